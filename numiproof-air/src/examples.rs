@@ -1,7 +1,8 @@
 // Additional AIR examples for production use
 use serde::{Serialize, Deserialize};
-use crate::Air;
-use numiproof_field::Fp;
+use crate::{Air, Assertion};
+use numiproof_field::{batch_inverse, ExtensionField, Fp};
+use numiproof_hash::poseidon2;
 
 /// Range check AIR: enforces that a value lies in [0, 2^bits - 1].
 /// Implements a running-division by 2 with boolean bit constraints and a power-of-two column.
@@ -26,13 +27,14 @@ impl RangeCheckAir {
 }
 
 impl Air for RangeCheckAir {
+    type Field = Fp;
     type PublicInput = RangeCheckPublic;
     
-    fn id(&self) -> &'static str { "range_check_v1" }
+    fn id() -> &'static str { "range_check_v1" }
     
-    fn trace_len(&self) -> usize { self.bits + 1 }
+    fn n_cols() -> usize { 3 } // [accumulator, bit, power_of_2]
     
-    fn n_cols(&self) -> usize { 3 } // [accumulator, bit, power_of_2]
+    fn trace_len(&self) -> usize { self.bits + 1 }
     
     fn public_input(&self) -> Self::PublicInput {
         RangeCheckPublic {
@@ -52,12 +54,12 @@ impl Air for RangeCheckAir {
         
         // Decompose into bits
         let mut remaining = self.value.to_u64();
-        let mut power = 1u64;
+        let mut power = Fp::one();
         
         for i in 0..self.bits {
             let b = remaining & 1;
             bit[i] = Fp::new(b);
-            pow2[i] = Fp::new(power);
+            pow2[i] = power;
             
             // Next accumulator: subtract current bit contribution
             if i + 1 < n {
@@ -65,77 +67,43 @@ impl Air for RangeCheckAir {
             }
             
             remaining >>= 1;
-            power <<= 1;
+            power += power;
         }
         
-        // Last row should have accumulator = 0
+        // Last row should have accumulator = 0; the power column keeps doubling so the
+        // transition into the last row holds as well
         acc[self.bits] = Fp::zero();
+        pow2[self.bits] = power;
         
         vec![acc, bit, pow2]
     }
     
-    fn check_row(i: usize, row: &[Fp], next: Option<&[Fp]>, pub_inp: &Self::PublicInput) -> bool {
-        if i == 0 {
-            // First row: accumulator equals value
-            if row[0].to_u64() != pub_inp.value {
-                return false;
-            }
-        }
-        
-        // Check bit is boolean (0 or 1)
-        let b = row[1];
-        if b != Fp::zero() && b != Fp::one() {
-            return false;
-        }
-        
-        if let Some(nxt) = next {
-            // Transition: next_acc = (acc - bit) / 2
-            let expected_next = (row[0] - row[1]) * Fp::new(2).inv();
-            if nxt[0] != expected_next {
-                return false;
-            }
-            
+    fn transition_degrees() -> Vec<usize> { vec![1, 2, 1] }
+    
+    fn eval_transition<E: ExtensionField>(row: &[E], next: &[E], _pub_inp: &Self::PublicInput) -> Vec<E> {
+        vec![
+            // next_acc = (acc - bit) / 2, multiplied through by 2
+            next[0] + next[0] - (row[0] - row[1]),
+            // Boolean constraint: bit * (bit - 1) = 0
+            row[1] * (row[1] - E::one()),
             // Power of 2 doubles each step
-            if nxt[2] != row[2] * Fp::new(2) {
-                return false;
-            }
-        } else {
-            // Last row: accumulator should be zero
-            if row[0] != Fp::zero() {
-                return false;
-            }
-        }
-        
-        true
+            next[2] - (row[2] + row[2]),
+        ]
     }
     
-    fn eval_constraints(&self, i: usize, row: &[Fp], next: Option<&[Fp]>, pub_inp: &Self::PublicInput) -> Vec<Fp> {
-        let mut constraints = vec![Fp::zero(); 3];
-        
-        if i == 0 {
-            // Boundary: first accumulator equals value
-            constraints[0] = row[0] - Fp::new(pub_inp.value);
-        }
-        
-        // Boolean constraint: bit * (bit - 1) = 0
-        let b = row[1];
-        constraints[1] = b * (b - Fp::one());
-        
-        if let Some(nxt) = next {
-            // Transition constraint
-            constraints[0] = nxt[0] - (row[0] - row[1]) * Fp::new(2).inv();
-            constraints[2] = nxt[2] - row[2] * Fp::new(2);
-        } else {
-            // Boundary: last accumulator is zero
-            constraints[0] = row[0];
-        }
-        
-        constraints
+    fn assertions(pub_inp: &Self::PublicInput) -> Vec<Assertion> {
+        vec![
+            // First accumulator equals value, last is zero
+            Assertion::new(0, 0, Fp::new(pub_inp.value)),
+            Assertion::new(pub_inp.bits as usize, 0, Fp::zero()),
+            Assertion::new(0, 2, Fp::one()),
+        ]
     }
 }
 
-/// Permutation-check AIR: enforces output is a permutation of input using a single running product
-/// with a fixed challenge beta (for simplicity in examples; real systems derive beta via Fiat–Shamir).
+/// Permutation-check AIR: enforces output is a permutation of input using a grand-product column
+/// `z_{i+1} = z_i · (in_i + beta) / (out_i + beta)` that starts and ends at 1, with a fixed
+/// challenge beta (for simplicity in examples; real systems derive beta via Fiat–Shamir).
 #[derive(Clone, Serialize, Deserialize)]
 pub struct PermutationPublic {
     pub input_hash: Vec<u8>,
@@ -154,16 +122,20 @@ impl PermutationAir {
         assert_eq!(input.len(), output.len());
         Self { input, output }
     }
+
+    /// Fixed permutation challenge.
+    const BETA: Fp = Fp(7);
 }
 
 impl Air for PermutationAir {
+    type Field = Fp;
     type PublicInput = PermutationPublic;
     
-    fn id(&self) -> &'static str { "permutation_v1" }
+    fn id() -> &'static str { "permutation_v2" }
     
-    fn trace_len(&self) -> usize { self.input.len() + 1 }
+    fn n_cols() -> usize { 3 } // [input_val, output_val, grand_product]
     
-    fn n_cols(&self) -> usize { 4 } // [input_val, output_val, product_in, product_out]
+    fn trace_len(&self) -> usize { self.input.len() + 1 }
     
     fn public_input(&self) -> Self::PublicInput {
         use numiproof_hash::shake256_384;
@@ -186,8 +158,7 @@ impl Air for PermutationAir {
         let n = self.trace_len();
         let mut input_col = vec![Fp::zero(); n];
         let mut output_col = vec![Fp::zero(); n];
-        let mut prod_in = vec![Fp::one(); n];
-        let mut prod_out = vec![Fp::one(); n];
+        let mut z = vec![Fp::one(); n];
         
         // Fill values
         for i in 0..self.input.len() {
@@ -195,76 +166,36 @@ impl Air for PermutationAir {
             output_col[i] = Fp::new(self.output[i]);
         }
         
-        // Compute running products with random challenge (beta)
-        // In practice, beta would come from Fiat-Shamir
-        let beta = Fp::new(7); // Simplified: fixed challenge
-        
-        for i in 0..self.input.len() {
-            let in_contribution = input_col[i] + beta;
-            let out_contribution = output_col[i] + beta;
-            
-            if i + 1 < n {
-                prod_in[i + 1] = prod_in[i] * in_contribution;
-                prod_out[i + 1] = prod_out[i] * out_contribution;
-            }
+        // Running ratio of the two products; returns to 1 exactly when output permutes input
+        let len = self.input.len();
+        let denoms: Vec<Fp> = output_col[..len].iter().map(|&o| o + Self::BETA).collect();
+        for (i, d_inv) in batch_inverse(&denoms).into_iter().enumerate() {
+            z[i + 1] = z[i] * (input_col[i] + Self::BETA) * d_inv;
         }
         
-        vec![input_col, output_col, prod_in, prod_out]
+        vec![input_col, output_col, z]
     }
     
-    fn check_row(i: usize, row: &[Fp], next: Option<&[Fp]>, _pub_inp: &Self::PublicInput) -> bool {
-        if i == 0 {
-            // First row: products start at 1
-            if row[2] != Fp::one() || row[3] != Fp::one() {
-                return false;
-            }
-        }
-        
-        if let Some(nxt) = next {
-            let beta = Fp::new(7);
-            
-            // Running product updates
-            let expected_prod_in = row[2] * (row[0] + beta);
-            let expected_prod_out = row[3] * (row[1] + beta);
-            
-            if nxt[2] != expected_prod_in || nxt[3] != expected_prod_out {
-                return false;
-            }
-        } else {
-            // Last row: products should be equal (permutation check)
-            if row[2] != row[3] {
-                return false;
-            }
-        }
-        
-        true
+    fn transition_degrees() -> Vec<usize> { vec![2] }
+    
+    fn eval_transition<E: ExtensionField>(row: &[E], next: &[E], _pub_inp: &Self::PublicInput) -> Vec<E> {
+        let beta = E::from(Self::BETA);
+        vec![next[2] * (row[1] + beta) - row[2] * (row[0] + beta)]
     }
     
-    fn eval_constraints(&self, i: usize, row: &[Fp], next: Option<&[Fp]>, _pub_inp: &Self::PublicInput) -> Vec<Fp> {
-        let mut constraints = vec![Fp::zero(); 4];
-        
-        if i == 0 {
-            // Boundary: initial products are 1
-            constraints[2] = row[2] - Fp::one();
-            constraints[3] = row[3] - Fp::one();
-        }
-        
-        if let Some(nxt) = next {
-            let beta = Fp::new(7);
-            
-            // Running product constraints
-            constraints[2] = nxt[2] - row[2] * (row[0] + beta);
-            constraints[3] = nxt[3] - row[3] * (row[1] + beta);
-        } else {
-            // Boundary: final products equal
-            constraints[2] = row[2] - row[3];
-        }
-        
-        constraints
+    fn assertions(pub_inp: &Self::PublicInput) -> Vec<Assertion> {
+        vec![
+            Assertion::new(0, 2, Fp::one()),
+            Assertion::new(pub_inp.length as usize, 2, Fp::one()),
+        ]
     }
 }
 
-/// Hash-chain AIR: proves correct computation of an iterated SHAKE256-384 over 6-limb field encoding.
+/// Hash-chain AIR: iterated SHAKE256-384 over a 6-limb field encoding.
+///
+/// SHAKE is not an algebraic relation over the field, so the transition "constraint" is not a
+/// low-degree polynomial: rows can be checked with `check_row`, but a STARK proof over this AIR
+/// does not pass the low-degree test. Provable chains use [`Poseidon2Air`] instead.
 #[derive(Clone, Serialize, Deserialize)]
 pub struct HashChainPublic {
     pub initial: Vec<u8>,
@@ -283,44 +214,49 @@ impl HashChainAir {
         Self { initial, iterations }
     }
     
-    fn hash_limbs(limbs: &[Fp; 6]) -> [Fp; 6] {
+    /// Hash of the first six limbs of `row`. Off the base field every coordinate of each limb is
+    /// hashed, which keeps the map well defined but no more algebraic.
+    fn hash_limbs<E: ExtensionField>(row: &[E]) -> [Fp; 6] {
         use numiproof_hash::shake256_384;
         
         // Convert limbs to bytes
-        let mut bytes = Vec::with_capacity(48);
-        for limb in limbs.iter() {
-            bytes.extend_from_slice(&limb.to_u64().to_le_bytes());
+        let mut bytes = Vec::with_capacity(48 * E::DEGREE);
+        for limb in row.iter().take(6) {
+            for c in limb.base_coeffs() {
+                bytes.extend_from_slice(&c.to_u64().to_le_bytes());
+            }
         }
         
         // Hash
         let hash = shake256_384(&bytes);
         
         // Convert back to limbs
-        let mut result = [Fp::zero(); 6];
-        for i in 0..6 {
-            let start = i * 8;
-            let mut chunk = [0u8; 8];
-            chunk.copy_from_slice(&hash[start..start + 8]);
-            result[i] = Fp::new(u64::from_le_bytes(chunk));
-        }
-        result
+        core::array::from_fn(|i| Self::limb(&hash, i).unwrap())
+    }
+
+    /// Little-endian 64-bit limb `j` of `bytes`, if the chunk is fully present.
+    fn limb(bytes: &[u8], j: usize) -> Option<Fp> {
+        let chunk = bytes.get(j * 8..j * 8 + 8)?;
+        Some(Fp::new(u64::from_le_bytes(chunk.try_into().unwrap())))
     }
 }
 
 impl Air for HashChainAir {
+    type Field = Fp;
     type PublicInput = HashChainPublic;
     
-    fn id(&self) -> &'static str { "hash_chain_v1" }
+    fn id() -> &'static str { "hash_chain_v1" }
     
-    fn trace_len(&self) -> usize { self.iterations + 1 }
+    fn n_cols() -> usize { 6 } // 6 limbs for 384-bit hash
     
-    fn n_cols(&self) -> usize { 6 } // 6 limbs for 384-bit hash
+    fn trace_len(&self) -> usize { self.iterations + 1 }
     
     fn public_input(&self) -> Self::PublicInput {
         use numiproof_hash::shake256_384;
         
-        // Compute final hash by iterating
+        // Compute final hash by iterating from the same 48-byte padded state as the trace
         let mut current = self.initial.clone();
+        current.resize(current.len().max(48), 0);
         for _ in 0..self.iterations {
             current = shake256_384(&current).to_vec();
         }
@@ -346,13 +282,8 @@ impl Air for HashChainAir {
         
         for step in 0..=self.iterations {
             // Decompose current hash into limbs
-            for i in 0..6 {
-                let start = i * 8;
-                let mut chunk = [0u8; 8];
-                if start + 8 <= current.len() {
-                    chunk.copy_from_slice(&current[start..start + 8]);
-                }
-                cols[i][step] = Fp::new(u64::from_le_bytes(chunk));
+            for (i, col) in cols.iter_mut().enumerate() {
+                col[step] = Self::limb(&current, i).unwrap_or(Fp::zero());
             }
             
             // Compute next hash for next iteration
@@ -364,88 +295,163 @@ impl Air for HashChainAir {
         cols
     }
     
-    fn check_row(i: usize, row: &[Fp], next: Option<&[Fp]>, pub_inp: &Self::PublicInput) -> bool {
-        if i == 0 {
-            // First row: verify initial state
-            for j in 0..6 {
-                let start = j * 8;
-                if start + 8 <= pub_inp.initial.len() {
-                    let mut chunk = [0u8; 8];
-                    chunk.copy_from_slice(&pub_inp.initial[start..start + 8]);
-                    let expected = Fp::new(u64::from_le_bytes(chunk));
-                    if row[j] != expected {
-                        return false;
-                    }
-                }
-            }
+    /// Nominal degrees only; see the type-level docs.
+    fn transition_degrees() -> Vec<usize> { vec![1; 6] }
+    
+    fn eval_transition<E: ExtensionField>(row: &[E], next: &[E], _pub_inp: &Self::PublicInput) -> Vec<E> {
+        // next = hash(current)
+        let expected_next = Self::hash_limbs(row);
+        next.iter().zip(expected_next.iter()).map(|(n, e)| *n - E::from(*e)).collect()
+    }
+    
+    fn assertions(pub_inp: &Self::PublicInput) -> Vec<Assertion> {
+        // Initial state on the first row, final hash on the last
+        let last = pub_inp.iterations as usize;
+        (0..6)
+            .filter_map(|j| Self::limb(&pub_inp.initial, j).map(|v| Assertion::new(0, j, v)))
+            .chain((0..6).filter_map(|j| Self::limb(&pub_inp.final_hash, j).map(|v| Assertion::new(last, j, v))))
+            .collect()
+    }
+}
+
+/// Poseidon2 chain AIR: row `i` holds a state `s_i` together with the S-box witnesses of one
+/// permutation, and the next row starts from `s_{i+1} = P(s_i)`, with `P` the width-8
+/// Goldilocks permutation of [`numiproof_hash::poseidon2`].
+///
+/// Every S-box `x -> x^7` gets two columns, `x^3` and `x^7`, constrained by `x^3 - x·x·x` and
+/// `x^7 - x^3·x^3·x`. The S-box inputs are affine in earlier columns (round constants and linear
+/// layers are applied inside the constraints), so every constraint has degree at most 3. The
+/// last round's output is tied linearly to the next row's state.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Poseidon2Public {
+    pub input: [u64; poseidon2::WIDTH],
+    pub output: [u64; poseidon2::WIDTH],
+    pub permutations: u32,
+}
+
+#[derive(Clone)]
+pub struct Poseidon2Air {
+    pub input: [Fp; poseidon2::WIDTH],
+    pub permutations: usize,
+}
+
+impl Poseidon2Air {
+    /// S-boxes per permutation: a full layer in every full round, one in every partial round.
+    const SBOXES: usize = poseidon2::ROUNDS_F * poseidon2::WIDTH + poseidon2::ROUNDS_P;
+
+    pub fn new(input: [u64; poseidon2::WIDTH], permutations: usize) -> Self {
+        assert!(permutations > 0);
+        Self { input: input.map(Fp::new), permutations }
+    }
+
+    /// Trace row for one permutation of `input` (the state, then `(x^3, x^7)` for every S-box in
+    /// round order) and the permuted state.
+    pub fn witness_row(input: &[Fp; poseidon2::WIDTH]) -> (Vec<Fp>, [Fp; poseidon2::WIDTH]) {
+        let rc = poseidon2::round_constants();
+        let mut row = Vec::with_capacity(Self::n_cols());
+        row.extend_from_slice(input);
+        let mut sbox = |x: Fp| {
+            let cube = x * x * x;
+            let out = cube * cube * x;
+            row.extend([cube, out]);
+            out
+        };
+        let mut state = *input;
+        poseidon2::external_linear_layer(&mut state);
+        let (first, last) = rc.external.split_at(poseidon2::ROUNDS_F / 2);
+        for r in first {
+            state.iter_mut().zip(r).for_each(|(x, &c)| *x = sbox(*x + c));
+            poseidon2::external_linear_layer(&mut state);
         }
-        
-        if let Some(nxt) = next {
-            // Verify hash transition
-            let current_limbs: [Fp; 6] = [row[0], row[1], row[2], row[3], row[4], row[5]];
-            let expected_next = Self::hash_limbs(&current_limbs);
-            
-            for j in 0..6 {
-                if nxt[j] != expected_next[j] {
-                    return false;
-                }
-            }
-        } else {
-            // Last row: verify final hash
-            for j in 0..6 {
-                let start = j * 8;
-                if start + 8 <= pub_inp.final_hash.len() {
-                    let mut chunk = [0u8; 8];
-                    chunk.copy_from_slice(&pub_inp.final_hash[start..start + 8]);
-                    let expected = Fp::new(u64::from_le_bytes(chunk));
-                    if row[j] != expected {
-                        return false;
-                    }
-                }
-            }
+        for &c in &rc.internal {
+            state[0] = sbox(state[0] + c);
+            poseidon2::internal_linear_layer(&mut state);
         }
-        
-        true
+        for r in last {
+            state.iter_mut().zip(r).for_each(|(x, &c)| *x = sbox(*x + c));
+            poseidon2::external_linear_layer(&mut state);
+        }
+        (row, state)
     }
-    
-    fn eval_constraints(&self, i: usize, row: &[Fp], next: Option<&[Fp]>, pub_inp: &Self::PublicInput) -> Vec<Fp> {
-        let mut constraints = vec![Fp::zero(); 6];
-        
-        if i == 0 {
-            // Boundary: initial state
-            for j in 0..6 {
-                let start = j * 8;
-                if start + 8 <= pub_inp.initial.len() {
-                    let mut chunk = [0u8; 8];
-                    chunk.copy_from_slice(&pub_inp.initial[start..start + 8]);
-                    let expected = Fp::new(u64::from_le_bytes(chunk));
-                    constraints[j] = row[j] - expected;
-                }
-            }
+}
+
+impl Air for Poseidon2Air {
+    type Field = Fp;
+    type PublicInput = Poseidon2Public;
+
+    fn id() -> &'static str { "poseidon2_chain_v1" }
+
+    fn n_cols() -> usize { poseidon2::WIDTH + 2 * Self::SBOXES }
+
+    fn trace_len(&self) -> usize { self.permutations + 1 }
+
+    fn public_input(&self) -> Self::PublicInput {
+        let mut state = self.input;
+        for _ in 0..self.permutations {
+            poseidon2::permute(&mut state);
         }
-        
-        if let Some(nxt) = next {
-            // Transition constraints: next = hash(current)
-            let current_limbs: [Fp; 6] = [row[0], row[1], row[2], row[3], row[4], row[5]];
-            let expected_next = Self::hash_limbs(&current_limbs);
-            
-            for j in 0..6 {
-                constraints[j] = nxt[j] - expected_next[j];
-            }
-        } else {
-            // Boundary: final hash
-            for j in 0..6 {
-                let start = j * 8;
-                if start + 8 <= pub_inp.final_hash.len() {
-                    let mut chunk = [0u8; 8];
-                    chunk.copy_from_slice(&pub_inp.final_hash[start..start + 8]);
-                    let expected = Fp::new(u64::from_le_bytes(chunk));
-                    constraints[j] = row[j] - expected;
-                }
-            }
+        Poseidon2Public {
+            input: self.input.map(Fp::to_u64),
+            output: state.map(Fp::to_u64),
+            permutations: self.permutations as u32,
         }
-        
-        constraints
+    }
+
+    fn gen_trace(&self) -> Vec<Vec<Fp>> {
+        let n = self.trace_len();
+        let mut cols = vec![Vec::with_capacity(n); Self::n_cols()];
+        // The last row permutes the output as well, so every row is a complete witness
+        let mut state = self.input;
+        for _ in 0..n {
+            let (row, next) = Self::witness_row(&state);
+            cols.iter_mut().zip(row).for_each(|(c, v)| c.push(v));
+            state = next;
+        }
+        cols
+    }
+
+    fn transition_degrees() -> Vec<usize> {
+        let mut d = vec![3; 2 * Self::SBOXES];
+        d.extend([1; poseidon2::WIDTH]);
+        d
+    }
+
+    fn eval_transition<E: ExtensionField>(row: &[E], next: &[E], _pub_inp: &Self::PublicInput) -> Vec<E> {
+        let rc = poseidon2::round_constants();
+        let mut out = Vec::with_capacity(2 * Self::SBOXES + poseidon2::WIDTH);
+        let mut witness = row[poseidon2::WIDTH..].chunks_exact(2);
+        let mut sbox = |x: E| {
+            let w = witness.next().expect("one witness pair per S-box");
+            let (cube, seventh) = (w[0], w[1]);
+            out.push(cube - x * x * x);
+            out.push(seventh - cube * cube * x);
+            seventh
+        };
+        let mut state: [E; poseidon2::WIDTH] = core::array::from_fn(|i| row[i]);
+        poseidon2::external_linear_layer(&mut state);
+        let (first, last) = rc.external.split_at(poseidon2::ROUNDS_F / 2);
+        for r in first {
+            state.iter_mut().zip(r).for_each(|(x, &c)| *x = sbox(*x + E::from(c)));
+            poseidon2::external_linear_layer(&mut state);
+        }
+        for &c in &rc.internal {
+            state[0] = sbox(state[0] + E::from(c));
+            poseidon2::internal_linear_layer(&mut state);
+        }
+        for r in last {
+            state.iter_mut().zip(r).for_each(|(x, &c)| *x = sbox(*x + E::from(c)));
+            poseidon2::external_linear_layer(&mut state);
+        }
+        out.extend(next.iter().zip(&state).map(|(&n, &s)| n - s));
+        out
+    }
+
+    fn assertions(pub_inp: &Self::PublicInput) -> Vec<Assertion> {
+        let last = pub_inp.permutations as usize;
+        (0..poseidon2::WIDTH)
+            .map(|j| Assertion::new(0, j, Fp::new(pub_inp.input[j])))
+            .chain((0..poseidon2::WIDTH).map(|j| Assertion::new(last, j, Fp::new(pub_inp.output[j]))))
+            .collect()
     }
 }
 
@@ -463,15 +469,21 @@ mod tests {
         assert_eq!(trace[0].len(), 9);
         
         // Verify first row has the value
-        assert_eq!(trace[0][0].to_u64(), 42);
+        assert_eq!(trace[0][0].to_u64(), pub_inp.value);
         // Verify last row has accumulator = 0
         assert_eq!(trace[0][8], Fp::zero());
         
         // Check bits are boolean
-        for i in 0..8 {
-            let bit = trace[1][i];
+        for &bit in &trace[1][..8] {
             assert!(bit == Fp::zero() || bit == Fp::one());
         }
+
+        // Every row satisfies the AIR, including the transition into the last row
+        let row = |i: usize| trace.iter().map(|c| c[i]).collect::<Vec<_>>();
+        for i in 0..air.trace_len() {
+            let next = if i + 1 < air.trace_len() { Some(row(i + 1)) } else { None };
+            assert!(RangeCheckAir::check_row(i, &row(i), next.as_deref(), &pub_inp));
+        }
     }
     
     #[test]
@@ -479,12 +491,26 @@ mod tests {
         let input = vec![1, 2, 3, 4, 5];
         let output = vec![5, 3, 1, 4, 2]; // Valid permutation
         let air = PermutationAir::new(input, output);
-        let _pub_inp = air.public_input();
+        let pub_inp = air.public_input();
         let trace = air.gen_trace();
         
-        // Last row should have equal products
+        // Grand product returns to one on the last row
         let last = trace[0].len() - 1;
-        assert_eq!(trace[2][last], trace[3][last]);
+        assert_eq!(trace[2][last], Fp::one());
+        let row = |i: usize| trace.iter().map(|c| c[i]).collect::<Vec<_>>();
+        for i in 0..air.trace_len() {
+            let next = if i + 1 < air.trace_len() { Some(row(i + 1)) } else { None };
+            assert!(PermutationAir::check_row(i, &row(i), next.as_deref(), &pub_inp));
+        }
+    }
+    
+    #[test]
+    fn permutation_check_rejects_non_permutation() {
+        let air = PermutationAir::new(vec![1, 2, 3], vec![1, 2, 4]);
+        let pub_inp = air.public_input();
+        let trace = air.gen_trace();
+        let last: Vec<Fp> = trace.iter().map(|c| c[3]).collect();
+        assert!(!PermutationAir::check_row(3, &last, None, &pub_inp));
     }
     
     #[test]
@@ -504,13 +530,79 @@ mod tests {
             initial_padded.push(0);
         }
         
-        for i in 0..6 {
+        for (i, col) in trace.iter().enumerate() {
             let start = i * 8;
             let mut chunk = [0u8; 8];
             chunk.copy_from_slice(&initial_padded[start..start + 8]);
             let expected = Fp::new(u64::from_le_bytes(chunk));
-            assert_eq!(trace[i][0], expected, "First row limb {} mismatch", i);
+            assert_eq!(col[0], expected, "First row limb {} mismatch", i);
         }
+
+        // Last row matches the public final hash
+        let last: Vec<Fp> = trace.iter().map(|c| c[3]).collect();
+        assert!(HashChainAir::check_row(3, &last, None, &pub_inp));
+    }
+
+    #[test]
+    fn poseidon2_trace_matches_native_permutation() {
+        let input = [0, 1, 2, 3, 4, 5, 6, 7];
+        let air = Poseidon2Air::new(input, 3);
+        let pub_inp = air.public_input();
+        let trace = air.gen_trace();
+        assert_eq!(trace.len(), Poseidon2Air::n_cols());
+        assert_eq!(Poseidon2Air::transition_degrees().len(), Poseidon2Air::n_cols());
+        let row = |i: usize| trace.iter().map(|c| c[i]).collect::<Vec<_>>();
+        let mut state = input.map(Fp::new);
+        for i in 0..air.trace_len() {
+            assert_eq!(&row(i)[..poseidon2::WIDTH], &state[..]);
+            let next = if i + 1 < air.trace_len() { Some(row(i + 1)) } else { None };
+            assert!(Poseidon2Air::check_row(i, &row(i), next.as_deref(), &pub_inp));
+            poseidon2::permute(&mut state);
+        }
+        // A wrong S-box witness or a wrong successor state breaks the constraints
+        let mut bad = row(1);
+        bad[poseidon2::WIDTH + 40] += Fp::one();
+        assert!(!Poseidon2Air::check_row(1, &bad, Some(&row(2)), &pub_inp));
+        let mut bad_next = row(2);
+        bad_next[3] += Fp::one();
+        assert!(!Poseidon2Air::check_row(1, &row(1), Some(&bad_next), &pub_inp));
     }
-}
 
+    #[test]
+    fn poseidon2_constraints_are_low_degree_polynomials() {
+        use numiproof_field::{Field, Fp3};
+        // Along the line row + t·dir each constraint is a polynomial in t of at most its declared
+        // degree, so its (degree + 1)-th finite difference vanishes
+        let air = Poseidon2Air::new([9, 8, 7, 6, 5, 4, 3, 2], 1);
+        let pub_inp = air.public_input();
+        let trace = air.gen_trace();
+        let n_cols = Poseidon2Air::n_cols();
+        let row: Vec<Fp3> = trace.iter().map(|c| Fp3::from(c[0])).collect();
+        let next: Vec<Fp3> = trace.iter().map(|c| Fp3::from(c[1])).collect();
+        let dir: Vec<Fp3> = (0..2 * n_cols as u64)
+            .map(|i| Fp3([Fp::new(i * 0x9e37_79b9 + 1), Fp::new(i ^ 0x5555), Fp::new(i * i + 3)]))
+            .collect();
+        let degrees = Poseidon2Air::transition_degrees();
+        let at = |t: u64| {
+            let t = Fp3::from(Fp::new(t));
+            let r: Vec<Fp3> = row.iter().zip(&dir).map(|(&x, &d)| x + t * d).collect();
+            let n: Vec<Fp3> = next.iter().zip(&dir[n_cols..]).map(|(&x, &d)| x + t * d).collect();
+            Poseidon2Air::eval_transition(&r, &n, &pub_inp)
+        };
+        let evals: Vec<Vec<Fp3>> = (0..5).map(at).collect();
+        assert!(evals[0].iter().all(|c| *c == Fp3::zero()));
+        // Finite difference of the given order of constraint `k` at t = 0
+        let diff = |k: usize, order: usize| {
+            let mut d: Vec<Fp3> = evals.iter().map(|e| e[k]).take(order + 1).collect();
+            for _ in 0..order {
+                d = d.windows(2).map(|w| w[1] - w[0]).collect();
+            }
+            d[0]
+        };
+        for (k, &deg) in degrees.iter().enumerate() {
+            assert_eq!(diff(k, deg + 1), Fp3::zero(), "constraint {k} exceeds degree {deg}");
+        }
+        // The S-box constraints really are cubic
+        assert_ne!(diff(0, 3), Fp3::zero());
+    }
+}
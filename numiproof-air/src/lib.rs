@@ -1,24 +1,61 @@
 // File: numiproof-air/src/lib.rs
 use serde::{Serialize, Deserialize};
-use numiproof_field::Fp;
+use numiproof_field::{ExtensionField, Field, Fp, PrimeField, TwoAdicField};
 
 pub mod examples;
 
+/// Boundary assertion: trace column `col` holds `value` at row `row`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Assertion<F = Fp> {
+    pub row: usize,
+    pub col: usize,
+    pub value: F,
+}
+
+impl<F> Assertion<F> {
+    pub fn new(row: usize, col: usize, value: F) -> Self { Self { row, col, value } }
+}
+
 pub trait Air {
-    type PublicInput: Serialize + for<'de> Deserialize<'de> + Clone;
-    fn id(&self) -> &'static str;
+    /// Field the trace is written in.
+    type Field: TwoAdicField;
+    type PublicInput: Serialize + for<'de> Deserialize<'de> + Clone + Send + Sync;
+    /// Stable identifier bound into the transcript and checked by the verifier.
+    fn id() -> &'static str;
+    /// Number of trace columns; fixed per AIR so the verifier never trusts a proof's row width.
+    fn n_cols() -> usize;
     fn trace_len(&self) -> usize;
-    fn n_cols(&self) -> usize;
     fn public_input(&self) -> Self::PublicInput;
-    fn gen_trace(&self) -> Vec<Vec<Fp>>; // column-major over field elements
-    fn check_row(i: usize, row: &[Fp], next: Option<&[Fp]>, pub_inp: &Self::PublicInput) -> bool;
-    /// Evaluate constraint polynomials for a given row (and optional next row).
-    /// Implementations should return zero when constraints are satisfied.
-    fn eval_constraints(&self, i: usize, row: &[Fp], next: Option<&[Fp]>, pub_inp: &Self::PublicInput) -> Vec<Fp>;
+    fn gen_trace(&self) -> Vec<Vec<Self::Field>>; // column-major over field elements
+    /// Algebraic degree of each transition constraint in the trace variables, in the order
+    /// returned by [`Air::eval_transition`].
+    fn transition_degrees() -> Vec<usize>;
+    /// Evaluate the transition constraints on two consecutive rows. They must vanish for every
+    /// `i < trace_len - 1` and be polynomials in the row values alone (no dependence on `i`), so
+    /// the prover can evaluate them on the LDE domain and the verifier at any query point,
+    /// including out-of-domain points in an extension `E` of the trace field.
+    fn eval_transition<E: ExtensionField<Self::Field>>(row: &[E], next: &[E], pub_inp: &Self::PublicInput) -> Vec<E>;
+    /// Boundary assertions pinning single trace cells to values derived from the public input.
+    fn assertions(pub_inp: &Self::PublicInput) -> Vec<Assertion<Self::Field>>;
+    /// Checks on the public input of an `n_rows` trace that no constraint expresses, such as
+    /// fields derived from others by hashing. The verifier rejects proofs failing them.
+    fn check_public_input(_pub_inp: &Self::PublicInput, _n_rows: usize) -> bool { true }
+    /// Check row `i` (and its successor, if any) against the transition constraints and the
+    /// assertions on that row. Witness sanity check only; proofs use the constraint polynomials.
+    fn check_row(i: usize, row: &[Self::Field], next: Option<&[Self::Field]>, pub_inp: &Self::PublicInput) -> bool {
+        let transition_ok = next.is_none_or(|nxt| {
+            Self::eval_transition(row, nxt, pub_inp).iter().all(|c| *c == Self::Field::zero())
+        });
+        transition_ok
+            && Self::assertions(pub_inp)
+                .iter()
+                .filter(|a| a.row == i)
+                .all(|a| row.get(a.col) == Some(&a.value))
+    }
 }
 
-/// Simple Fibonacci AIR over the Goldilocks field with wrapping arithmetic.
-/// Columns are [a_i, a_{i+1}]. The last-row boundary fixes a_i to the expected-first value.
+/// Simple Fibonacci AIR over any two-adic field (Goldilocks by default) with wrapping arithmetic.
+/// Columns are [a_i, a_{i+1}]. Row 0 holds the inputs and row `steps` fixes a_i to the expected-first value.
 #[derive(Clone, Serialize, Deserialize)]
 pub struct FibPublic {
     pub steps: u32,
@@ -27,36 +64,41 @@ pub struct FibPublic {
     pub expected_first: u64, // a_steps in field, encoded as u64
 }
 #[derive(Clone)]
-pub struct FibonacciAir {
-    pub a0: Fp,
-    pub a1: Fp,
+pub struct FibonacciAir<F = Fp> {
+    pub a0: F,
+    pub a1: F,
     pub steps: usize,
 }
 impl FibonacciAir {
-    pub fn new(a0: u64, a1: u64, steps: usize) -> Self { Self { a0: Fp::new(a0), a1: Fp::new(a1), steps } }
-    fn expected_first(a0: Fp, a1: Fp, steps: usize) -> Fp {
+    pub fn new(a0: u64, a1: u64, steps: usize) -> Self { Self::in_field(a0, a1, steps) }
+}
+impl<F: TwoAdicField> FibonacciAir<F> {
+    /// Same sequence over the field `F`; the inputs are reduced modulo its prime.
+    pub fn in_field(a0: u64, a1: u64, steps: usize) -> Self { Self { a0: F::from_u64(a0), a1: F::from_u64(a1), steps } }
+    fn expected_first(a0: F, a1: F, steps: usize) -> F {
         let mut x=a0; let mut y=a1;
         for _ in 0..steps { let z = x + y; x=y; y=z; }
         x
     }
 }
-impl Air for FibonacciAir {
+impl<F: TwoAdicField> Air for FibonacciAir<F> {
+    type Field = F;
     type PublicInput = FibPublic;
-    fn id(&self) -> &'static str { "fibonacci_v1" }
+    fn id() -> &'static str { "fibonacci_v1" }
+    fn n_cols() -> usize { 2 }
     fn trace_len(&self) -> usize { self.steps+1 }
-    fn n_cols(&self) -> usize { 2 }
     fn public_input(&self) -> Self::PublicInput {
         FibPublic {
             steps: self.steps as u32,
-            a0: self.a0.to_u64(),
-            a1: self.a1.to_u64(),
-            expected_first: Self::expected_first(self.a0, self.a1, self.steps).to_u64(),
+            a0: self.a0.as_canonical_u64(),
+            a1: self.a1.as_canonical_u64(),
+            expected_first: Self::expected_first(self.a0, self.a1, self.steps).as_canonical_u64(),
         }
     }
-    fn gen_trace(&self) -> Vec<Vec<Fp>> {
+    fn gen_trace(&self) -> Vec<Vec<F>> {
         let n = self.trace_len();
-        let mut c0 = vec![Fp::zero(); n];
-        let mut c1 = vec![Fp::zero(); n];
+        let mut c0 = vec![F::zero(); n];
+        let mut c1 = vec![F::zero(); n];
         c0[0] = self.a0; c1[0] = self.a1;
         for i in 0..n-1 {
             c0[i+1] = c1[i];
@@ -64,35 +106,23 @@ impl Air for FibonacciAir {
         }
         vec![c0, c1]
     }
-    fn check_row(i: usize, row: &[Fp], next: Option<&[Fp]>, pub_inp: &Self::PublicInput) -> bool {
-        if i==0 && (row[0].to_u64()!=pub_inp.a0 || row[1].to_u64()!=pub_inp.a1) { return false; }
-        if let Some(nxt) = next {
-            if nxt[0] != row[1] { return false; }
-            if nxt[1] != row[0] + row[1] { return false; }
-        } else {
-            // Last row boundary: a_i equals expected_first
-            if row[0].to_u64() != pub_inp.expected_first { return false; }
-        }
-        true
+    fn transition_degrees() -> Vec<usize> { vec![1, 1] }
+    fn eval_transition<E: ExtensionField<F>>(row: &[E], next: &[E], _pub_inp: &Self::PublicInput) -> Vec<E> {
+        vec![next[0] - row[1], next[1] - (row[0] + row[1])]
     }
-    fn eval_constraints(&self, _i: usize, row: &[Fp], next: Option<&[Fp]>, pub_inp: &Self::PublicInput) -> Vec<Fp> {
-        if let Some(nxt) = next {
-            // Transition constraints
-            let c0 = nxt[0] - row[1];
-            let c1 = nxt[1] - (row[0] + row[1]);
-            vec![c0, c1]
-        } else {
-            // Last-row boundary constraint
-            let c_end = row[0] - Fp::new(pub_inp.expected_first);
-            // Provide two entries to match column count for simple composers
-            vec![c_end, Fp::zero()]
-        }
+    fn assertions(pub_inp: &Self::PublicInput) -> Vec<Assertion<F>> {
+        vec![
+            Assertion::new(0, 0, F::from_u64(pub_inp.a0)),
+            Assertion::new(0, 1, F::from_u64(pub_inp.a1)),
+            Assertion::new(pub_inp.steps as usize, 0, F::from_u64(pub_inp.expected_first)),
+        ]
     }
 }
 
-pub fn row_to_bytes(row: &[Fp]) -> Vec<u8> {
-    let mut v = Vec::with_capacity(8*row.len());
-    for &x in row { v.extend_from_slice(&x.to_u64().to_le_bytes()); }
+/// Canonical encoding of a trace row, `F::NUM_BYTES` bytes per value.
+pub fn row_to_bytes<F: PrimeField>(row: &[F]) -> Vec<u8> {
+    let mut v = Vec::with_capacity(F::NUM_BYTES * row.len());
+    for &x in row { x.write_bytes(&mut v); }
     v
 }
 
@@ -134,7 +164,23 @@ mod tests {
         let i = 2;
         let row = [trace[0][i], trace[1][i]];
         let mut bad_next = [trace[0][i+1], trace[1][i+1]];
-        bad_next[1] = bad_next[1] + Fp::one();
+        bad_next[1] += Fp::one();
         assert!(!FibonacciAir::check_row(i, &row, Some(&bad_next), &pub_inp));
     }
+
+    #[test]
+    fn fib_over_babybear_wraps_modulo_its_prime() {
+        use numiproof_field::BabyBear;
+        let air = FibonacciAir::<BabyBear>::in_field(1, 1, 60);
+        let pub_inp = air.public_input();
+        // F_60 exceeds 2^31, so the public value differs from the Goldilocks one
+        assert!(pub_inp.expected_first < BabyBear::MODULUS);
+        assert_ne!(pub_inp.expected_first, FibonacciAir::new(1, 1, 60).public_input().expected_first);
+        let trace = air.gen_trace();
+        for i in 0..air.trace_len() {
+            let row = [trace[0][i], trace[1][i]];
+            let next = (i + 1 < air.trace_len()).then(|| [trace[0][i + 1], trace[1][i + 1]]);
+            assert!(FibonacciAir::<BabyBear>::check_row(i, &row, next.as_ref().map(|r| &r[..]), &pub_inp));
+        }
+    }
 }
\ No newline at end of file
@@ -1,11 +1,11 @@
 // File: numiproof-cli/src/main.rs
 use clap::{Parser, Subcommand};
 use numiproof_air::{FibonacciAir, Air};
-use numiproof_proof::{Prover, Verifier, accumulate, FriConfig};
+use numiproof_proof::{Prover, Verifier, VerifierConfig, accumulate, FriConfig, HashFunction, MAX_GRINDING_BITS};
 use numiproof_recursion::RecursiveAir;
 use numiproof_privacy as privacy;
 use numiproof_spec as spec;
-use std::{fs, path::PathBuf};
+use std::{fmt::Display, fs, path::PathBuf, process};
 
 #[derive(Parser)]
 #[command(name="numiproof", version, about="Minimal PQ-friendly STARK-style proof demo with FRI")]
@@ -33,6 +33,12 @@ enum Cmd {
         /// Number of FRI folding rounds
         #[arg(long, default_value_t=1)]
         fri_rounds: u32,
+        /// Hash backend: shake256-384, shake256-256, blake3 or poseidon2
+        #[arg(long, default_value_t=HashFunction::default())]
+        hash: HashFunction,
+        /// Proof-of-work bits ground before query sampling
+        #[arg(long, default_value_t=16, value_parser = clap::value_parser!(u32).range(..=MAX_GRINDING_BITS as i64))]
+        grinding_bits: u32,
         /// Optional params file (toml) to override FRI settings
         #[arg(long)]
         params: Option<PathBuf>,
@@ -41,6 +47,15 @@ enum Cmd {
     VerifyFib {
         #[arg(long, default_value = "proof.bin")]
         proof: PathBuf,
+        /// Fewest proof-of-work bits to accept
+        #[arg(long, default_value_t=16, value_parser = clap::value_parser!(u32).range(..=MAX_GRINDING_BITS as i64))]
+        min_grinding_bits: u32,
+        /// Fewest queries to accept (the prove-fib default)
+        #[arg(long, default_value_t=32)]
+        min_queries: usize,
+        /// Smallest LDE blowup to accept, as log2 (the prove-fib default)
+        #[arg(long, default_value_t=2)]
+        min_blowup_log2: u32,
     },
     /// Accumulate the proof digest into a rolling accumulator (demo)
     Accumulate {
@@ -71,36 +86,50 @@ enum Cmd {
     },
 }
 
+/// Report a user-input error and exit with the same status clap uses for bad arguments.
+fn fail(msg: impl Display) -> ! {
+    eprintln!("error: {msg}");
+    process::exit(2)
+}
+
 fn main() {
     let cli = Cli::parse();
     match cli.cmd {
-        Cmd::ProveFib { a0, a1, steps, out, queries, blowup_log2, fri_rounds, params } => {
+        Cmd::ProveFib { a0, a1, steps, out, queries, blowup_log2, fri_rounds, hash, grinding_bits, params } => {
             let air = FibonacciAir::new(a0, a1, steps);
             let cfg = if let Some(p) = params {
                 let txt = fs::read_to_string(p).expect("read params");
-                let p = spec::load_params_toml(&txt).expect("parse params");
+                let p = spec::load_params_toml(&txt).unwrap_or_else(|e| fail(format_args!("invalid params: {e}")));
                 FriConfig {
                     blowup_log2: p.blowup_log2.unwrap_or(blowup_log2),
                     num_rounds: p.fri_rounds.unwrap_or(fri_rounds),
                     queries: p.queries.unwrap_or(queries),
+                    hash: p.hash.map_or(Ok(hash), |h| h.parse()).unwrap_or_else(|e| fail(format_args!("invalid params: {e}"))),
+                    grinding_bits: p.grinding_bits.unwrap_or(grinding_bits),
                 }
             } else {
-                FriConfig { blowup_log2, num_rounds: fri_rounds, queries }
+                FriConfig { blowup_log2, num_rounds: fri_rounds, queries, hash, grinding_bits }
             };
             let prover = Prover { cfg };
-            let proof = prover.prove_fib(&air);
+            let proof = prover.prove(&air);
             // Streamed bincode writing
             let mut f = fs::File::create(&out).expect("create");
             bincode::serialize_into(&mut f, &proof).expect("encode");
             println!("wrote {}", out.display());
-            if let Some(ref fri) = proof.fri_commitment { println!("fri_root={} len={}", hex::encode(&fri.oracle.root), fri.oracle.len); }
-            if let Some(ref rounds) = proof.fri_rounds { for (i, r) in rounds.rounds.iter().enumerate() { println!("fri_round[{}]_root={} len={}", i, hex::encode(&r.root), r.len); } }
+            println!("hash={}", proof.hash);
+            println!("grinding_bits={} pow_nonce={}", proof.grinding_bits, proof.pow_nonce);
+            println!("composition_root={}", hex::encode(&proof.deep.composition_root));
+            for (i, r) in proof.fri_proof.commitment.rounds.iter().enumerate() { println!("fri_round[{}]_root={} len={}", i, hex::encode(&r.root), r.len); }
+            println!("fri_final_poly_len={}", proof.fri_proof.final_poly.len());
         }
-        Cmd::VerifyFib { proof } => {
+        Cmd::VerifyFib { proof, min_grinding_bits, min_queries, min_blowup_log2 } => {
             let f = fs::File::open(&proof).expect("open");
             let proof: numiproof_proof::Proof = bincode::deserialize_from(f).expect("decode");
-            let ok = Verifier::verify_fib(&proof);
-            println!("{}", if ok { "valid" } else { "invalid" });
+            let policy = VerifierConfig { min_grinding_bits, min_queries, min_blowup_log2 };
+            match Verifier::verify_with::<FibonacciAir>(&proof, &policy) {
+                Ok(()) => println!("valid"),
+                Err(e) => println!("invalid: {e}"),
+            }
         }
         Cmd::Accumulate { current_proof, prev_hex } => {
             let f = fs::File::open(&current_proof).expect("open");
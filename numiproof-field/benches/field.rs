@@ -0,0 +1,65 @@
+// File: numiproof-field/benches/field.rs
+//! Goldilocks multiplication with the specialized reduction against the generic 128-bit modulo,
+//! and batch inversion against one inversion per element.
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use numiproof_field::{batch_inverse, Fp, MODULUS};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+const N: usize = 1 << 12;
+
+/// Multiplication as it was before the specialized reduction.
+#[inline]
+fn mul_reference(a: Fp, b: Fp) -> Fp {
+    Fp(((a.0 as u128 * b.0 as u128) % MODULUS as u128) as u64)
+}
+
+fn inputs() -> (Vec<Fp>, Vec<Fp>) {
+    let mut rng = StdRng::seed_from_u64(1);
+    let mut v = || (0..N).map(|_| Fp::new(rng.gen())).collect::<Vec<_>>();
+    (v(), v())
+}
+
+fn bench_mul(c: &mut Criterion) {
+    let (a, b) = inputs();
+    let mut group = c.benchmark_group("fp_mul_4096");
+    group.bench_function("goldilocks_reduction", |bench| {
+        bench.iter(|| a.iter().zip(&b).fold(Fp::one(), |acc, (x, y)| acc + *x * *y))
+    });
+    group.bench_function("u128_modulo", |bench| {
+        bench.iter(|| a.iter().zip(&b).fold(Fp::one(), |acc, (x, y)| acc + mul_reference(*x, *y)))
+    });
+    group.finish();
+}
+
+fn bench_from_u128(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(2);
+    let xs: Vec<u128> = (0..N).map(|_| rng.gen()).collect();
+    let mut group = c.benchmark_group("fp_from_u128_4096");
+    group.bench_function("goldilocks_reduction", |bench| {
+        bench.iter(|| xs.iter().fold(Fp::zero(), |acc, &x| acc + Fp::from_u128(black_box(x))))
+    });
+    group.bench_function("u128_modulo", |bench| {
+        bench.iter(|| xs.iter().fold(Fp::zero(), |acc, &x| acc + Fp((black_box(x) % MODULUS as u128) as u64)))
+    });
+    group.finish();
+}
+
+fn bench_pow(c: &mut Criterion) {
+    c.bench_function("fp_inv", |bench| {
+        bench.iter_batched(|| Fp::new(0x1234_5678_9ABC_DEF0), |x| x.inv(), BatchSize::SmallInput)
+    });
+}
+
+fn bench_batch_inverse(c: &mut Criterion) {
+    let (a, _) = inputs();
+    let mut group = c.benchmark_group("fp_inverse_4096");
+    group.bench_function("batch_inverse", |bench| bench.iter(|| batch_inverse(black_box(&a))));
+    group.bench_function("per_element", |bench| {
+        bench.iter(|| black_box(&a).iter().map(|x| x.inv()).collect::<Vec<_>>())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_mul, bench_from_u128, bench_pow, bench_batch_inverse);
+criterion_main!(benches);
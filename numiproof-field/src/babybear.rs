@@ -0,0 +1,165 @@
+// File: numiproof-field/src/babybear.rs
+//! The BabyBear field, `p = 15·2^27 + 1`. Elements fit in 32 bits and the multiplicative group
+//! has two-adicity 27, so it supports FFTs up to `2^27` points with cheap arithmetic.
+use core::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{Field, PrimeField, TwoAdicField};
+
+const P: u32 = 0x7800_0001;
+
+/// BabyBear element in canonical form `0..P`; like [`crate::Fp`], serialized as its canonical
+/// value and rejected on deserialization if out of range.
+#[derive(Copy, Clone, Default, Eq, PartialEq, Debug)]
+pub struct BabyBear(pub u32);
+
+impl BabyBear {
+    #[inline]
+    pub fn new(x: u32) -> Self { BabyBear(x % P) }
+}
+
+impl Serialize for BabyBear {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> { self.0.serialize(s) }
+}
+
+impl<'de> Deserialize<'de> for BabyBear {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let x = u32::deserialize(d)?;
+        if x < P { Ok(BabyBear(x)) } else { Err(D::Error::custom("non-canonical BabyBear element")) }
+    }
+}
+
+impl Add for BabyBear {
+    type Output = Self;
+    #[inline]
+    fn add(self, rhs: Self) -> Self {
+        // Both operands are below 2^31, so the sum cannot overflow
+        let s = self.0 + rhs.0;
+        BabyBear(if s >= P { s - P } else { s })
+    }
+}
+impl AddAssign for BabyBear { #[inline] fn add_assign(&mut self, rhs: Self) { *self = *self + rhs; } }
+
+impl Sub for BabyBear {
+    type Output = Self;
+    #[inline]
+    fn sub(self, rhs: Self) -> Self {
+        let (d, b) = self.0.overflowing_sub(rhs.0);
+        BabyBear(if b { d.wrapping_add(P) } else { d })
+    }
+}
+impl SubAssign for BabyBear { #[inline] fn sub_assign(&mut self, rhs: Self) { *self = *self - rhs; } }
+
+impl Mul for BabyBear {
+    type Output = Self;
+    #[inline]
+    fn mul(self, rhs: Self) -> Self { BabyBear(((self.0 as u64 * rhs.0 as u64) % P as u64) as u32) }
+}
+impl MulAssign for BabyBear { #[inline] fn mul_assign(&mut self, rhs: Self) { *self = *self * rhs; } }
+
+impl Neg for BabyBear {
+    type Output = Self;
+    #[inline]
+    fn neg(self) -> Self { if self.0 == 0 { self } else { BabyBear(P - self.0) } }
+}
+
+impl Field for BabyBear {
+    #[inline]
+    fn zero() -> Self { BabyBear(0) }
+    #[inline]
+    fn one() -> Self { BabyBear(1) }
+    fn inv(self) -> Self {
+        // Fermat's little theorem: a^(p-2)
+        self.pow(P as u128 - 2)
+    }
+}
+
+impl PrimeField for BabyBear {
+    const MODULUS: u64 = P as u64;
+    const NUM_BYTES: usize = 4;
+    #[inline]
+    fn from_u64(x: u64) -> Self { BabyBear((x % P as u64) as u32) }
+    #[inline]
+    fn as_canonical_u64(self) -> u64 { self.0 as u64 }
+    #[inline]
+    fn halve(self) -> Self {
+        if self.0 & 1 == 0 { BabyBear(self.0 >> 1) } else { BabyBear((self.0 >> 1) + (P >> 1) + 1) }
+    }
+}
+
+impl TwoAdicField for BabyBear {
+    const TWO_ADICITY: u32 = 27;
+    #[inline]
+    fn generator() -> Self { BabyBear(31) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn arithmetic_matches_u64_modulo() {
+        let mut rng = StdRng::seed_from_u64(31);
+        let p = P as u64;
+        for _ in 0..2000 {
+            let (a, b) = (rng.gen::<u64>() % p, rng.gen::<u64>() % p);
+            let (fa, fb) = (BabyBear::from_u64(a), BabyBear::from_u64(b));
+            assert_eq!((fa + fb).0 as u64, (a + b) % p);
+            assert_eq!((fa - fb).0 as u64, (a + p - b) % p);
+            assert_eq!((fa * fb).0 as u64, a * b % p);
+            assert_eq!((-fa + fa).0, 0);
+            if a != 0 { assert_eq!(fa * fa.inv(), BabyBear::one()); }
+            assert_eq!(fa.halve() + fa.halve(), fa);
+        }
+        assert_eq!(BabyBear::from_u64(u64::MAX).0 as u64, u64::MAX % p);
+    }
+
+    #[test]
+    fn generator_and_roots_of_unity() {
+        let p1 = P as u128 - 1;
+        let g = BabyBear::generator();
+        // p - 1 = 2^27 · 3 · 5
+        for f in [2, 3, 5] {
+            assert_ne!(g.pow(p1 / f), BabyBear::one());
+        }
+        for bits in [1, 10, 27] {
+            let w = BabyBear::root_of_unity(bits);
+            assert_eq!(w.pow(1 << bits), BabyBear::one());
+            assert_eq!(w.pow(1 << (bits - 1)), -BabyBear::one());
+        }
+    }
+
+    #[test]
+    fn sqrt_and_legendre() {
+        let mut rng = StdRng::seed_from_u64(16);
+        let g = BabyBear::generator();
+        for _ in 0..256 {
+            let a = BabyBear::new(rng.gen());
+            let sq = a * a;
+            let r = sq.sqrt().unwrap();
+            assert!(r == a || r == -a);
+            if a != BabyBear::zero() {
+                assert_eq!(sq.legendre(), 1);
+                assert!(!(sq * g).is_square());
+                assert_eq!((sq * g).sqrt(), None);
+            }
+        }
+        let w = BabyBear::root_of_unity(26);
+        assert_eq!(w.sqrt().map(|r| r * r), Some(w));
+    }
+
+    #[test]
+    fn canonical_bytes() {
+        let x = BabyBear(P - 1);
+        let mut bytes = Vec::new();
+        x.write_bytes(&mut bytes);
+        assert_eq!(bytes, (P - 1).to_le_bytes());
+        assert_eq!(BabyBear::from_canonical_bytes(&bytes), Some(x));
+        assert_eq!(BabyBear::from_canonical_bytes(&P.to_le_bytes()), None);
+        assert_eq!(BabyBear::from_canonical_bytes(&[0; 8]), None);
+        assert_eq!(bincode::deserialize::<BabyBear>(&bincode::serialize(&x).unwrap()).unwrap(), x);
+        assert!(bincode::deserialize::<BabyBear>(&P.to_le_bytes()).is_err());
+    }
+}
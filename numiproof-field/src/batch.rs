@@ -0,0 +1,179 @@
+// File: numiproof-field/src/batch.rs
+//! Slice-level arithmetic: Montgomery batch inversion, and element-wise operations over
+//! fixed-width packs of lanes so the compiler can keep several independent reductions in flight
+//! and vectorize them.
+use core::ops::{Add, Mul, Sub};
+
+use crate::Field;
+
+/// Number of lanes in a [`Packed`] value.
+pub const PACK_WIDTH: usize = 8;
+
+/// `PACK_WIDTH` field elements operated on lane by lane.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Packed<F>(pub [F; PACK_WIDTH]);
+
+impl<F: Field> Packed<F> {
+    /// Load the first `PACK_WIDTH` elements of `xs`.
+    #[inline]
+    pub fn from_slice(xs: &[F]) -> Self { Packed(core::array::from_fn(|i| xs[i])) }
+
+    /// Every lane set to `x`.
+    #[inline]
+    pub fn broadcast(x: F) -> Self { Packed([x; PACK_WIDTH]) }
+
+    /// Store the lanes into the first `PACK_WIDTH` elements of `out`.
+    #[inline]
+    pub fn write_to(self, out: &mut [F]) { out[..PACK_WIDTH].copy_from_slice(&self.0); }
+
+    /// Sum of all lanes.
+    #[inline]
+    pub fn sum(self) -> F { self.0.into_iter().fold(F::zero(), |acc, x| acc + x) }
+}
+
+impl<F: Field> Add for Packed<F> {
+    type Output = Self;
+    #[inline]
+    fn add(self, rhs: Self) -> Self { Packed(core::array::from_fn(|i| self.0[i] + rhs.0[i])) }
+}
+
+impl<F: Field> Sub for Packed<F> {
+    type Output = Self;
+    #[inline]
+    fn sub(self, rhs: Self) -> Self { Packed(core::array::from_fn(|i| self.0[i] - rhs.0[i])) }
+}
+
+/// Lane-wise product; the right-hand side may be a pack of a subfield.
+impl<F: Field + Mul<S, Output = F>, S: Copy> Mul<Packed<S>> for Packed<F> {
+    type Output = Self;
+    #[inline]
+    fn mul(self, rhs: Packed<S>) -> Self { Packed(core::array::from_fn(|i| self.0[i] * rhs.0[i])) }
+}
+
+/// Inverses of `values` with a single field inversion (Montgomery's trick); zeros map to zero,
+/// as with [`Field::inv`].
+pub fn batch_inverse<F: Field>(values: &[F]) -> Vec<F> {
+    let mut out = values.to_vec();
+    batch_inverse_in_place(&mut out);
+    out
+}
+
+/// In-place form of [`batch_inverse`].
+pub fn batch_inverse_in_place<F: Field>(values: &mut [F]) {
+    // prefix[i] is the product of the non-zero values before index i
+    let mut prefix = Vec::with_capacity(values.len());
+    let mut acc = F::one();
+    for &v in values.iter() {
+        prefix.push(acc);
+        if v != F::zero() { acc *= v; }
+    }
+    let mut acc_inv = acc.inv();
+    for (v, p) in values.iter_mut().zip(prefix).rev() {
+        if *v == F::zero() { continue; }
+        let inv = acc_inv * p;
+        acc_inv *= *v;
+        *v = inv;
+    }
+}
+
+/// `a[i] += b[i]` for every `i`; the slices must have the same length.
+pub fn add_assign_slices<F: Field>(a: &mut [F], b: &[F]) {
+    assert_eq!(a.len(), b.len(), "slice lengths differ");
+    let mut a_packs = a.chunks_exact_mut(PACK_WIDTH);
+    let mut b_packs = b.chunks_exact(PACK_WIDTH);
+    for (x, y) in a_packs.by_ref().zip(b_packs.by_ref()) {
+        (Packed::from_slice(x) + Packed::from_slice(y)).write_to(x);
+    }
+    for (x, &y) in a_packs.into_remainder().iter_mut().zip(b_packs.remainder()) {
+        *x += y;
+    }
+}
+
+/// `a[i] *= s` for every `i`; `s` may lie in a subfield of the values.
+pub fn scale_slice<F: Field + Mul<S, Output = F>, S: Copy>(a: &mut [F], s: S) {
+    let s_pack = Packed([s; PACK_WIDTH]);
+    let mut packs = a.chunks_exact_mut(PACK_WIDTH);
+    for x in packs.by_ref() {
+        (Packed::from_slice(x) * s_pack).write_to(x);
+    }
+    for x in packs.into_remainder() {
+        *x = *x * s;
+    }
+}
+
+/// `sum_i a[i] · b[i]`; the slices must have the same length and `b` may lie in a subfield.
+pub fn dot_product<F: Field + Mul<S, Output = F>, S: Copy>(a: &[F], b: &[S]) -> F {
+    assert_eq!(a.len(), b.len(), "slice lengths differ");
+    // One running sum per lane keeps the additions independent
+    let mut acc = Packed::broadcast(F::zero());
+    let mut a_packs = a.chunks_exact(PACK_WIDTH);
+    let mut b_packs = b.chunks_exact(PACK_WIDTH);
+    for (x, y) in a_packs.by_ref().zip(b_packs.by_ref()) {
+        acc = acc + Packed::from_slice(x) * Packed(core::array::from_fn(|i| y[i]));
+    }
+    a_packs.remainder().iter().zip(b_packs.remainder()).fold(acc.sum(), |s, (&x, &y)| s + x * y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BabyBear, Fp, Fp3, PrimeField};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn random<F: PrimeField>(rng: &mut StdRng, n: usize) -> Vec<F> {
+        (0..n).map(|_| F::from_u64(rng.gen())).collect()
+    }
+
+    fn check_batch_inverse<F: PrimeField>(seed: u64) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        for n in [0, 1, 2, 7, 64, 101] {
+            let mut xs: Vec<F> = random(&mut rng, n);
+            // Zeros anywhere, including both ends, are skipped
+            for i in (0..n).step_by(5) { xs[i] = F::zero(); }
+            if n > 0 { xs[n - 1] = F::zero(); }
+            let expected: Vec<F> = xs.iter().map(|x| x.inv()).collect();
+            assert_eq!(batch_inverse(&xs), expected);
+        }
+    }
+
+    #[test]
+    fn batch_inverse_matches_inv() {
+        check_batch_inverse::<Fp>(1);
+        check_batch_inverse::<BabyBear>(2);
+        assert_eq!(batch_inverse(&[Fp::zero(); 3]), vec![Fp::zero(); 3]);
+    }
+
+    #[test]
+    fn slice_helpers_match_scalar_loops() {
+        let mut rng = StdRng::seed_from_u64(3);
+        for n in [0, 5, PACK_WIDTH, 3 * PACK_WIDTH + 3] {
+            let a: Vec<Fp> = random(&mut rng, n);
+            let b: Vec<Fp> = random(&mut rng, n);
+            let s = Fp::new(rng.gen());
+
+            let mut sum = a.clone();
+            add_assign_slices(&mut sum, &b);
+            assert!(sum.iter().zip(a.iter().zip(&b)).all(|(r, (x, y))| *r == *x + *y));
+
+            let mut scaled = a.clone();
+            scale_slice(&mut scaled, s);
+            assert!(scaled.iter().zip(&a).all(|(r, x)| *r == *x * s));
+
+            let dot = a.iter().zip(&b).fold(Fp::zero(), |acc, (x, y)| acc + *x * *y);
+            assert_eq!(dot_product(&a, &b), dot);
+        }
+    }
+
+    #[test]
+    fn slice_helpers_mix_extension_and_base() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let base: Vec<Fp> = random(&mut rng, 19);
+        let ext: Vec<Fp3> = (0..19).map(|_| Fp3(core::array::from_fn(|_| Fp::new(rng.gen())))).collect();
+        let dot = ext.iter().zip(&base).fold(Fp3::zero(), |acc, (x, y)| acc + *x * *y);
+        assert_eq!(dot_product(&ext, &base), dot);
+        let mut scaled = ext.clone();
+        scale_slice(&mut scaled, base[0]);
+        assert!(scaled.iter().zip(&ext).all(|(r, x)| *r == *x * base[0]));
+    }
+}
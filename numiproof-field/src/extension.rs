@@ -0,0 +1,280 @@
+// File: numiproof-field/src/extension.rs
+//! Binomial extensions: of the Goldilocks field, `Fp2 = Fp[u]/(u^2 - 7)` and
+//! `Fp3 = Fp[u]/(u^3 - 7)`, where 7 generates the multiplicative group, so it is neither a
+//! square nor a cube and both polynomials are irreducible; of BabyBear, `BabyBear4 =
+//! F[u]/(u^4 - 11)`, irreducible because 11 is a non-square and `p = 1 mod 4`.
+use core::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use serde::{Deserialize, Serialize};
+
+use crate::{BabyBear, Field, Fp, PrimeField};
+
+/// Non-residue defining both Goldilocks extensions.
+const W: Fp = Fp(7);
+/// Primitive cube root of unity `7^((p-1)/3)`; Frobenius on `Fp3` scales `u` by it.
+const OMEGA: Fp = Fp(0xFFFF_FFFE_0000_0001);
+const OMEGA_SQ: Fp = Fp(0xFFFF_FFFF);
+
+/// Non-residue defining the BabyBear extension.
+const W_BB: BabyBear = BabyBear(11);
+/// Powers of the primitive fourth root of unity `11^((p-1)/4)`; Frobenius on `BabyBear4`
+/// scales `u^i` by the `i`-th.
+const I_BB: [BabyBear; 4] = [BabyBear(1), BabyBear(0x6705_5C21), BabyBear(0x7800_0000), BabyBear(0x10FA_A3E0)];
+
+/// A field containing the prime field `F`: `F` itself or one of its extensions. Challenges and
+/// out-of-domain points are drawn from it, while traces stay in `F`.
+pub trait ExtensionField<F: PrimeField = Fp>: Field + From<F> + Mul<F, Output = Self> {
+    /// Degree of the extension over `F`.
+    const DEGREE: usize;
+
+    /// Element with coordinates `coeffs` (low to high) in the basis `1, u, u^2, ...`.
+    fn from_base_slice(coeffs: &[F]) -> Self;
+    /// Coordinates over `F`, `DEGREE` of them.
+    fn base_coeffs(&self) -> &[F];
+    /// The `p`-power map `x -> x^p`.
+    fn frobenius(self) -> Self;
+}
+
+impl<F: PrimeField> ExtensionField<F> for F {
+    const DEGREE: usize = 1;
+    fn from_base_slice(coeffs: &[F]) -> Self { coeffs[0] }
+    fn base_coeffs(&self) -> &[F] { core::slice::from_ref(self) }
+    fn frobenius(self) -> Self { self }
+}
+
+/// Element `c[0] + c[1]·u` of the quadratic extension, `u^2 = 7`.
+#[derive(Copy, Clone, Default, Serialize, Deserialize, Eq, PartialEq, Debug)]
+pub struct Fp2(pub [Fp; 2]);
+
+/// Element `c[0] + c[1]·u + c[2]·u^2` of the cubic extension, `u^3 = 7`.
+#[derive(Copy, Clone, Default, Serialize, Deserialize, Eq, PartialEq, Debug)]
+pub struct Fp3(pub [Fp; 3]);
+
+/// Element `c[0] + ... + c[3]·u^3` of the quartic extension of BabyBear, `u^4 = 11`.
+#[derive(Copy, Clone, Default, Serialize, Deserialize, Eq, PartialEq, Debug)]
+pub struct BabyBear4(pub [BabyBear; 4]);
+
+/// Coordinate-wise operations and the embedding of the base field, shared by all extensions.
+macro_rules! impl_linear_ops {
+    ($t:ident, $f:ident, $n:literal) => {
+        impl From<$f> for $t {
+            #[inline]
+            fn from(x: $f) -> Self {
+                let mut c = [<$f as Field>::zero(); $n];
+                c[0] = x;
+                $t(c)
+            }
+        }
+        impl Add for $t {
+            type Output = Self;
+            #[inline]
+            fn add(self, rhs: Self) -> Self { $t(core::array::from_fn(|i| self.0[i] + rhs.0[i])) }
+        }
+        impl Sub for $t {
+            type Output = Self;
+            #[inline]
+            fn sub(self, rhs: Self) -> Self { $t(core::array::from_fn(|i| self.0[i] - rhs.0[i])) }
+        }
+        impl Neg for $t {
+            type Output = Self;
+            #[inline]
+            fn neg(self) -> Self { $t(self.0.map(|c| -c)) }
+        }
+        impl Mul<$f> for $t {
+            type Output = Self;
+            #[inline]
+            fn mul(self, rhs: $f) -> Self { $t(self.0.map(|c| c * rhs)) }
+        }
+        impl AddAssign for $t { #[inline] fn add_assign(&mut self, rhs: Self) { *self = *self + rhs; } }
+        impl SubAssign for $t { #[inline] fn sub_assign(&mut self, rhs: Self) { *self = *self - rhs; } }
+        impl MulAssign for $t { #[inline] fn mul_assign(&mut self, rhs: Self) { *self = *self * rhs; } }
+    };
+}
+impl_linear_ops!(Fp2, Fp, 2);
+impl_linear_ops!(Fp3, Fp, 3);
+impl_linear_ops!(BabyBear4, BabyBear, 4);
+
+impl Mul for Fp2 {
+    type Output = Self;
+    #[inline]
+    fn mul(self, rhs: Self) -> Self {
+        let [a0, a1] = self.0;
+        let [b0, b1] = rhs.0;
+        Fp2([a0 * b0 + W * a1 * b1, a0 * b1 + a1 * b0])
+    }
+}
+
+impl Mul for Fp3 {
+    type Output = Self;
+    #[inline]
+    fn mul(self, rhs: Self) -> Self {
+        let [a0, a1, a2] = self.0;
+        let [b0, b1, b2] = rhs.0;
+        Fp3([
+            a0 * b0 + W * (a1 * b2 + a2 * b1),
+            a0 * b1 + a1 * b0 + W * a2 * b2,
+            a0 * b2 + a1 * b1 + a2 * b0,
+        ])
+    }
+}
+
+impl Mul for BabyBear4 {
+    type Output = Self;
+    #[inline]
+    fn mul(self, rhs: Self) -> Self {
+        let (a, b) = (self.0, rhs.0);
+        let mut c = [BabyBear::zero(); 4];
+        for i in 0..4 {
+            for j in 0..4 {
+                // u^(i+j) wraps to 11·u^(i+j-4)
+                if i + j < 4 { c[i + j] += a[i] * b[j]; } else { c[i + j - 4] += W_BB * a[i] * b[j]; }
+            }
+        }
+        BabyBear4(c)
+    }
+}
+
+impl Field for Fp2 {
+    fn zero() -> Self { Fp2([Fp::zero(); 2]) }
+    fn one() -> Self { Fp2::from(Fp::one()) }
+    fn inv(self) -> Self {
+        // x · conj(x) = a0^2 - 7·a1^2 lies in Fp
+        let [a0, a1] = self.0;
+        let norm_inv = (a0 * a0 - W * a1 * a1).inv();
+        Fp2([a0 * norm_inv, -a1 * norm_inv])
+    }
+}
+
+impl ExtensionField for Fp2 {
+    const DEGREE: usize = 2;
+    fn from_base_slice(coeffs: &[Fp]) -> Self { Fp2([coeffs[0], coeffs[1]]) }
+    fn base_coeffs(&self) -> &[Fp] { &self.0 }
+    fn frobenius(self) -> Self {
+        // u^p = u · 7^((p-1)/2) = -u
+        Fp2([self.0[0], -self.0[1]])
+    }
+}
+
+impl Field for Fp3 {
+    fn zero() -> Self { Fp3([Fp::zero(); 3]) }
+    fn one() -> Self { Fp3::from(Fp::one()) }
+    fn inv(self) -> Self {
+        // The norm x · x^p · x^(p^2) lies in Fp, so x^-1 = x^p · x^(p^2) / norm
+        let f1 = self.frobenius();
+        let conj = f1 * f1.frobenius();
+        let norm = (self * conj).0[0];
+        conj * norm.inv()
+    }
+}
+
+impl ExtensionField for Fp3 {
+    const DEGREE: usize = 3;
+    fn from_base_slice(coeffs: &[Fp]) -> Self { Fp3([coeffs[0], coeffs[1], coeffs[2]]) }
+    fn base_coeffs(&self) -> &[Fp] { &self.0 }
+    fn frobenius(self) -> Self {
+        // u^p = u · 7^((p-1)/3)
+        let [a0, a1, a2] = self.0;
+        Fp3([a0, a1 * OMEGA, a2 * OMEGA_SQ])
+    }
+}
+
+impl Field for BabyBear4 {
+    fn zero() -> Self { BabyBear4([BabyBear::zero(); 4]) }
+    fn one() -> Self { BabyBear4::from(BabyBear::one()) }
+    fn inv(self) -> Self {
+        // As for Fp3: the product of the three conjugates over the norm
+        let f1 = self.frobenius();
+        let f2 = f1.frobenius();
+        let conj = f1 * f2 * f2.frobenius();
+        let norm = (self * conj).0[0];
+        conj * norm.inv()
+    }
+}
+
+impl ExtensionField<BabyBear> for BabyBear4 {
+    const DEGREE: usize = 4;
+    fn from_base_slice(coeffs: &[BabyBear]) -> Self { BabyBear4([coeffs[0], coeffs[1], coeffs[2], coeffs[3]]) }
+    fn base_coeffs(&self) -> &[BabyBear] { &self.0 }
+    fn frobenius(self) -> Self {
+        // u^p = u · 11^((p-1)/4)
+        BabyBear4(core::array::from_fn(|i| self.0[i] * I_BB[i]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MODULUS;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn random<F: PrimeField, E: ExtensionField<F>>(rng: &mut StdRng) -> E {
+        let coeffs: Vec<F> = (0..E::DEGREE).map(|_| F::from_u64(rng.gen())).collect();
+        E::from_base_slice(&coeffs)
+    }
+
+    fn check_field<F: PrimeField, E: ExtensionField<F>>(seed: u64) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        for _ in 0..200 {
+            let (a, b, c): (E, E, E) = (random(&mut rng), random(&mut rng), random(&mut rng));
+            assert_eq!(a * (b + c), a * b + a * c);
+            assert_eq!((a * b) * c, a * (b * c));
+            assert_eq!(a - b + b, a);
+            assert_eq!(a + (-a), E::zero());
+            assert_eq!(a * E::inv(a), E::one());
+            assert_eq!(a.frobenius(), a.pow(F::MODULUS as u128));
+            let s = F::from_u64(rng.gen());
+            assert_eq!(a * s, a * E::from(s));
+        }
+        assert_eq!(E::inv(E::zero()), E::zero());
+    }
+
+    #[test]
+    fn quadratic_extension_is_a_field() {
+        check_field::<Fp, Fp2>(1);
+        check_field::<Fp, Fp>(2);
+    }
+
+    #[test]
+    fn cubic_extension_is_a_field() {
+        check_field::<Fp, Fp3>(3);
+    }
+
+    #[test]
+    fn babybear_quartic_extension_is_a_field() {
+        check_field::<BabyBear, BabyBear4>(4);
+        check_field::<BabyBear, BabyBear>(5);
+        let p1 = BabyBear::MODULUS as u128 - 1;
+        assert_eq!(W_BB.pow(p1 / 2), -BabyBear::one());
+        assert_eq!(W_BB.pow(p1 / 4), I_BB[1]);
+        assert_eq!(I_BB[1] * I_BB[1], I_BB[2]);
+        assert_eq!(I_BB[1] * I_BB[2], I_BB[3]);
+        let u = BabyBear4([BabyBear::zero(), BabyBear::one(), BabyBear::zero(), BabyBear::zero()]);
+        assert_eq!(u.pow(4), BabyBear4::from(W_BB));
+    }
+
+    #[test]
+    fn defining_polynomials_are_irreducible() {
+        let p1 = MODULUS as u128 - 1;
+        // 7 is a non-square and a non-cube, and OMEGA is the matching cube root of unity
+        assert_eq!(W.pow(p1 / 2), -Fp::one());
+        assert_eq!(W.pow(p1 / 3), OMEGA);
+        assert_eq!(OMEGA * OMEGA, OMEGA_SQ);
+        assert_eq!(Fp2([Fp::zero(), Fp::one()]).pow(2), Fp2::from(W));
+        assert_eq!(Fp3([Fp::zero(), Fp::one(), Fp::zero()]).pow(3), Fp3::from(W));
+        // Frobenius fixes exactly the base field
+        let x = Fp3([Fp::new(5), Fp::new(6), Fp::zero()]);
+        assert_ne!(x.frobenius(), x);
+        assert_eq!(x.frobenius().frobenius().frobenius(), x);
+        assert_eq!(Fp2::from(Fp::new(9)).frobenius(), Fp2::from(Fp::new(9)));
+    }
+
+    #[test]
+    fn serde_round_trip() {
+        let x = Fp3([Fp::new(1), Fp::new(2), Fp::new(3)]);
+        let bytes = bincode::serialize(&x).unwrap();
+        assert_eq!(bytes.len(), 24);
+        assert_eq!(bincode::deserialize::<Fp3>(&bytes).unwrap(), x);
+        let y = Fp2([Fp::new(u64::MAX - 1), Fp::new(7)]);
+        assert_eq!(bincode::deserialize::<Fp2>(&bincode::serialize(&y).unwrap()).unwrap(), y);
+    }
+}
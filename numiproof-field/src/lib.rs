@@ -1,12 +1,29 @@
 use core::ops::{Add, AddAssign, Sub, SubAssign, Mul, MulAssign, Neg};
-use serde::{Deserialize, Serialize};
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+
+mod traits;
+pub use traits::{Field, PrimeField, TwoAdicField};
+mod extension;
+pub use extension::{BabyBear4, ExtensionField, Fp2, Fp3};
+mod babybear;
+pub use babybear::BabyBear;
+mod batch;
+pub use batch::{add_assign_slices, batch_inverse, batch_inverse_in_place, dot_product, scale_slice, Packed, PACK_WIDTH};
 
 /// Goldilocks prime modulus (2^64 - 2^32 + 1), widely used for 64-bit FFTs.
 pub const MODULUS: u64 = 0xFFFF_FFFF_0000_0001;
 
-#[derive(Copy, Clone, Default, Serialize, Deserialize, Eq, PartialEq, Debug)]
+/// Goldilocks element, always kept in canonical form `0..MODULUS`. Serialized as its canonical
+/// `u64`; deserialization rejects values `>= MODULUS`, so every element has one encoding.
+#[derive(Copy, Clone, Default, Eq, PartialEq, Debug)]
 pub struct Fp(pub u64);
 
+/// Generator of the multiplicative group of Goldilocks; also its default coset shift.
+pub const GENERATOR: Fp = Fp(7);
+
+/// The Goldilocks field under its usual name.
+pub type Goldilocks = Fp;
+
 impl Fp {
     #[inline]
     pub fn new(x: u64) -> Self { Fp(reduce_u128(x as u128)) }
@@ -23,6 +40,21 @@ impl Fp {
     #[inline]
     pub fn to_u64(self) -> u64 { self.0 }
 
+    /// The canonical value, or `None` if `x >= MODULUS`.
+    #[inline]
+    pub fn from_canonical_u64(x: u64) -> Option<Self> { (x < MODULUS).then_some(Fp(x)) }
+
+    /// Canonical 8-byte little-endian encoding.
+    #[inline]
+    pub fn to_bytes(self) -> [u8; 8] { self.0.to_le_bytes() }
+
+    /// Strict inverse of [`Fp::to_bytes`]: exactly 8 bytes holding a value below `MODULUS`.
+    #[inline]
+    pub fn try_from_bytes(bytes: &[u8]) -> Option<Self> {
+        let bytes: [u8; 8] = bytes.try_into().ok()?;
+        Self::from_canonical_u64(u64::from_le_bytes(bytes))
+    }
+
     #[inline]
     pub fn inv(self) -> Self {
         // Fermat's little theorem: a^(p-2)
@@ -42,10 +74,41 @@ impl Fp {
     }
 }
 
+/// `2^64 - MODULUS = 2^32 - 1`, so `2^64 ≡ EPSILON (mod p)`.
+const EPSILON: u64 = 0xFFFF_FFFF;
+
+/// Reduce a 128-bit value to its canonical representative without a 128-bit division.
+/// Writing `x = lo + 2^64·hi_lo + 2^96·hi_hi`, we have `2^64 ≡ 2^32 - 1` and `2^96 ≡ -1`, so
+/// `x ≡ lo - hi_hi + hi_lo·(2^32 - 1)`, which fits in two 64-bit steps with carry fix-ups.
 #[inline]
 fn reduce_u128(x: u128) -> u64 {
-    // Correct reduction using native 128-bit remainder; fast enough for tests and correctness-critical.
-    (x % (MODULUS as u128)) as u64
+    let lo = x as u64;
+    let hi = (x >> 64) as u64;
+    let hi_hi = hi >> 32;
+    let hi_lo = hi & EPSILON;
+    let (mut t0, borrow) = lo.overflowing_sub(hi_hi);
+    if borrow {
+        // Wrapped by 2^64 ≡ EPSILON; lo < 2^32 here, so this cannot underflow again
+        t0 = t0.wrapping_sub(EPSILON);
+    }
+    let t1 = hi_lo * EPSILON;
+    let (mut r, carry) = t0.overflowing_add(t1);
+    if carry {
+        // r < t1 < 2^64 - 2^32 after wrapping, so adding EPSILON cannot overflow
+        r = r.wrapping_add(EPSILON);
+    }
+    if r >= MODULUS { r - MODULUS } else { r }
+}
+
+impl Serialize for Fp {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> { self.0.serialize(s) }
+}
+
+impl<'de> Deserialize<'de> for Fp {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let x = u64::deserialize(d)?;
+        Fp::from_canonical_u64(x).ok_or_else(|| D::Error::custom("non-canonical Goldilocks element"))
+    }
 }
 
 impl Add for Fp {
@@ -84,13 +147,42 @@ impl Neg for Fp {
     fn neg(self) -> Self::Output { if self.0 == 0 { self } else { Fp(MODULUS - self.0) } }
 }
 
-/// Compute a principal 2^k root of unity and its table of powers.
+impl Field for Fp {
+    #[inline]
+    fn zero() -> Self { Fp::zero() }
+    #[inline]
+    fn one() -> Self { Fp::one() }
+    #[inline]
+    fn inv(self) -> Self { Fp::inv(self) }
+    #[inline]
+    fn pow(self, e: u128) -> Self { Fp::pow(self, e) }
+}
+
+impl PrimeField for Fp {
+    const MODULUS: u64 = MODULUS;
+    const NUM_BYTES: usize = 8;
+    #[inline]
+    fn from_u64(x: u64) -> Self { Fp::new(x) }
+    #[inline]
+    fn as_canonical_u64(self) -> u64 { self.0 }
+    #[inline]
+    fn halve(self) -> Self {
+        // (x + p) / 2 for odd x, computed without overflowing
+        if self.0 & 1 == 0 { Fp(self.0 >> 1) } else { Fp((self.0 >> 1) + (MODULUS >> 1) + 1) }
+    }
+}
+
+impl TwoAdicField for Fp {
+    const TWO_ADICITY: u32 = 32;
+    #[inline]
+    fn generator() -> Self { GENERATOR }
+}
+
+/// Compute a principal 2^k root of unity of the Goldilocks field.
 pub fn root_of_unity(power: u32) -> Fp {
     // Known 2-adicity for Goldilocks is 32. We derive a principal 2^power root from generator g=7
     // as w = g^((p-1)/2^power). In debug builds we assert the expected order.
-    let g = Fp::new(7);
-    let exp = ((MODULUS as u128) - 1) >> power;
-    let w = g.pow(exp);
+    let w = <Fp as TwoAdicField>::root_of_unity(power);
     // As a safety net in debug/tests, assert expected order
     debug_assert_eq!(w.pow(1u128 << power), Fp::one());
     w
@@ -139,6 +231,31 @@ mod tests {
         }
     }
 
+    #[test]
+    fn reduce_u128_edge_cases() {
+        let p = MODULUS as u128;
+        let cases = [
+            0,
+            1,
+            p - 1,
+            p,
+            p + 1,
+            u64::MAX as u128,
+            1u128 << 64,
+            (1u128 << 96) - 1,
+            1u128 << 96,
+            (p - 1) * (p - 1),
+            (u64::MAX as u128) * (u64::MAX as u128),
+            u128::MAX,
+            // Borrow path: tiny low word, large top limb
+            (u32::MAX as u128) << 96,
+            ((u32::MAX as u128) << 96) | ((u32::MAX as u128) << 64),
+        ];
+        for x in cases {
+            assert_eq!(super::reduce_u128(x) as u128, x % p, "x = {x:#x}");
+        }
+    }
+
     #[test]
     fn mul_matches_big_mod() {
         let mut rng = StdRng::seed_from_u64(42);
@@ -159,7 +276,7 @@ mod tests {
         for _ in 0..2000 {
             let mut a: u64 = rng.gen();
             // avoid zero
-            if a % MODULUS == 0 { a = 1; }
+            if a == 0 || a == MODULUS { a = 1; }
             let fa = Fp::new(a);
             assert_eq!(fa * fa.inv(), Fp::one());
         }
@@ -186,6 +303,81 @@ mod tests {
         }
     }
 
+    #[test]
+    fn halve_and_canonical_bytes() {
+        let mut rng = StdRng::seed_from_u64(5);
+        for _ in 0..512 {
+            let a = Fp::new(rng.gen());
+            assert_eq!(a.halve() + a.halve(), a);
+            let mut bytes = Vec::new();
+            a.write_bytes(&mut bytes);
+            assert_eq!(bytes, a.0.to_le_bytes());
+            assert_eq!(Fp::from_canonical_bytes(&bytes), Some(a));
+        }
+        assert_eq!((-Fp::one()).halve(), -Fp::new(2).inv());
+        assert_eq!(Fp::from_canonical_bytes(&MODULUS.to_le_bytes()), None);
+        assert_eq!(Fp::from_canonical_bytes(&[1, 2, 3]), None);
+    }
+
+    #[test]
+    fn generator_has_full_order() {
+        // p - 1 = 2^32 · 3 · 5 · 17 · 257 · 65537
+        let p1 = MODULUS as u128 - 1;
+        for f in [2, 3, 5, 17, 257, 65537] {
+            assert_ne!(GENERATOR.pow(p1 / f), Fp::one());
+        }
+        assert_eq!(GENERATOR.pow(p1), Fp::one());
+        assert_eq!(Fp::coset_shift(), GENERATOR);
+    }
+
+    #[test]
+    fn sqrt_and_legendre() {
+        let mut rng = StdRng::seed_from_u64(16);
+        for _ in 0..256 {
+            let a = Fp::new(rng.gen());
+            let sq = a * a;
+            assert!(sq.is_square());
+            let r = sq.sqrt().unwrap();
+            assert!(r == a || r == -a);
+            // The generator is a non-square, so a non-zero square times it is one as well
+            if a != Fp::zero() {
+                assert_eq!(sq.legendre(), 1);
+                assert_eq!((sq * GENERATOR).legendre(), -1);
+                assert_eq!((sq * GENERATOR).sqrt(), None);
+            }
+        }
+        assert_eq!(Fp::zero().legendre(), 0);
+        assert_eq!(Fp::zero().sqrt(), Some(Fp::zero()));
+        // Roots of unity of high 2-adic order exercise every Tonelli–Shanks round
+        for bits in [1, 2, 16, 31] {
+            let w = root_of_unity(bits);
+            let r = w.sqrt().unwrap();
+            assert_eq!(r * r, w);
+        }
+        assert_eq!(root_of_unity(32).sqrt(), None);
+    }
+
+    #[test]
+    fn strict_bytes_and_serde() {
+        for x in [0, 1, 0xDEAD_BEEF, MODULUS - 1] {
+            let a = Fp(x);
+            assert_eq!(Fp::try_from_bytes(&a.to_bytes()), Some(a));
+            let enc = bincode::serialize(&a).unwrap();
+            assert_eq!(enc, a.to_bytes());
+            assert_eq!(bincode::deserialize::<Fp>(&enc).unwrap(), a);
+        }
+        for x in [MODULUS, MODULUS + 1, u64::MAX] {
+            assert_eq!(Fp::from_canonical_u64(x), None);
+            assert_eq!(Fp::try_from_bytes(&x.to_le_bytes()), None);
+            assert!(bincode::deserialize::<Fp>(&x.to_le_bytes()).is_err());
+        }
+        assert_eq!(Fp::try_from_bytes(&[0; 7]), None);
+        assert_eq!(Fp::try_from_bytes(&[0; 9]), None);
+        // Extensions inherit the strictness coordinate-wise
+        let enc = bincode::serialize(&[1u64, MODULUS, 2]).unwrap();
+        assert!(bincode::deserialize::<Fp3>(&enc).is_err());
+    }
+
     #[test]
     fn bit_reverse_permutation_roundtrip() {
         for bits in 1..=12 {
@@ -0,0 +1,128 @@
+// File: numiproof-field/src/traits.rs
+//! Field abstractions the rest of the stack is generic over. `Field` is the arithmetic shared by
+//! prime fields and their extensions, `PrimeField` adds the canonical integer representation and
+//! `TwoAdicField` the smooth multiplicative subgroups FFTs and FRI run on.
+use core::fmt::Debug;
+use core::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use serde::{de::DeserializeOwned, Serialize};
+
+pub trait Field:
+    Copy
+    + Default
+    + Debug
+    + Eq
+    + Send
+    + Sync
+    + Serialize
+    + DeserializeOwned
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Neg<Output = Self>
+    + AddAssign
+    + SubAssign
+    + MulAssign
+    + 'static
+{
+    fn zero() -> Self;
+    fn one() -> Self;
+    /// Multiplicative inverse; zero maps to zero.
+    fn inv(self) -> Self;
+
+    fn pow(self, mut e: u128) -> Self {
+        let mut base = self;
+        let mut acc = Self::one();
+        while e > 0 {
+            if e & 1 == 1 { acc *= base; }
+            base *= base;
+            e >>= 1;
+        }
+        acc
+    }
+}
+
+/// A prime field `Z/pZ` with `p < 2^64`, elements kept in canonical form `0..p`.
+pub trait PrimeField: Field {
+    const MODULUS: u64;
+    /// Length of the canonical little-endian encoding.
+    const NUM_BYTES: usize;
+
+    /// Reduce an arbitrary 64-bit integer.
+    fn from_u64(x: u64) -> Self;
+    /// Canonical representative in `0..MODULUS`.
+    fn as_canonical_u64(self) -> u64;
+    /// `self / 2`, without an inversion.
+    fn halve(self) -> Self;
+
+    /// Append the canonical `NUM_BYTES`-byte little-endian encoding to `out`.
+    fn write_bytes(self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.as_canonical_u64().to_le_bytes()[..Self::NUM_BYTES]);
+    }
+
+    /// Legendre symbol: `0` for zero, `1` for a non-zero square, `-1` for a non-square.
+    fn legendre(self) -> i8 {
+        let l = self.pow(((Self::MODULUS - 1) / 2) as u128);
+        if l == Self::zero() { 0 } else if l == Self::one() { 1 } else { -1 }
+    }
+
+    /// Whether `self` has a square root (zero counts).
+    fn is_square(self) -> bool { self.legendre() >= 0 }
+
+    /// Decode exactly `NUM_BYTES` little-endian bytes; values `>= MODULUS` are rejected.
+    fn from_canonical_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != Self::NUM_BYTES { return None; }
+        let mut buf = [0u8; 8];
+        buf[..bytes.len()].copy_from_slice(bytes);
+        let v = u64::from_le_bytes(buf);
+        (v < Self::MODULUS).then(|| Self::from_u64(v))
+    }
+}
+
+/// A prime field whose multiplicative group has a subgroup of order `2^TWO_ADICITY`.
+pub trait TwoAdicField: PrimeField {
+    /// Largest `k` with `2^k | p - 1`.
+    const TWO_ADICITY: u32;
+
+    /// Generator of the full multiplicative group; also the default coset shift, as it lies
+    /// outside every proper subgroup.
+    fn generator() -> Self;
+
+    /// Shift of the cosets LDEs are evaluated on. It lies outside every proper subgroup, so such
+    /// a coset never meets the trace domain and vanishing polynomials never vanish on it.
+    fn coset_shift() -> Self { Self::generator() }
+
+    /// Principal `2^bits`-th root of unity, `generator^((p-1) / 2^bits)`.
+    fn root_of_unity(bits: u32) -> Self {
+        assert!(bits <= Self::TWO_ADICITY, "no subgroup of order 2^{bits}");
+        Self::generator().pow(((Self::MODULUS - 1) >> bits) as u128)
+    }
+
+    /// A square root of `self`, or `None` for a non-square; the other root is its negation.
+    /// Tonelli–Shanks over the 2-Sylow subgroup, with the generator as the non-residue.
+    fn sqrt(self) -> Option<Self> {
+        if self == Self::zero() { return Some(self); }
+        if self.legendre() != 1 { return None; }
+        // p - 1 = q · 2^s with q odd
+        let q = ((Self::MODULUS - 1) >> Self::TWO_ADICITY) as u128;
+        let mut m = Self::TWO_ADICITY;
+        let mut c = Self::root_of_unity(m);
+        let mut t = self.pow(q);
+        let mut r = self.pow(q.div_ceil(2));
+        // Invariant: r^2 = self · t, and t has order dividing 2^(m-1)
+        while t != Self::one() {
+            let mut i = 0;
+            let mut t2 = t;
+            while t2 != Self::one() {
+                t2 *= t2;
+                i += 1;
+            }
+            let mut b = c;
+            for _ in 0..m - i - 1 { b *= b; }
+            r *= b;
+            c = b * b;
+            t *= c;
+            m = i;
+        }
+        Some(r)
+    }
+}
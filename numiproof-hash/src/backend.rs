@@ -0,0 +1,311 @@
+// File: numiproof-hash/src/backend.rs
+//! Hash backends for commitments and Fiat–Shamir. Every backend implements [`MerkleHasher`] and
+//! [`TranscriptHash`]; a proof records the [`HashFunction`] it was made with, trading digest
+//! size and recursion cost against the post-quantum security margin.
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use sha3::{digest::{ExtendableOutput, Update, XofReader}, Shake256, Shake256Reader};
+
+use crate::{poseidon2, DOM_MERKLE_NODE};
+
+/// Hash for Merkle trees: leaf hashes and the parent of two sibling digests.
+pub trait MerkleHasher: Sync {
+    /// Length in bytes of every digest.
+    fn digest_len(&self) -> usize;
+    /// Hash `parts` under `label`, each part prefixed by its index.
+    fn hash_many(&self, label: &str, parts: &[&[u8]]) -> Vec<u8>;
+    /// Parent of two sibling digests.
+    fn hash_node(&self, left: &[u8], right: &[u8]) -> Vec<u8> { self.hash_many(DOM_MERKLE_NODE, &[left, right]) }
+    /// Whether `bytes` is a well-formed digest; paths carrying anything else are rejected.
+    fn is_digest(&self, bytes: &[u8]) -> bool { bytes.len() == self.digest_len() }
+}
+
+/// Extendable-output hash driving a [`Transcript`](crate::Transcript).
+pub trait TranscriptHash {
+    type Reader: XofReader;
+    /// Bytes of transcript state kept between operations.
+    fn state_len(&self) -> usize;
+    /// Output stream of the hash of `data`.
+    fn xof(&self, data: &[u8]) -> Self::Reader;
+}
+
+/// `label` followed by the index-prefixed `parts`: the message every backend hashes.
+fn framed(label: &str, parts: &[&[u8]]) -> Vec<u8> {
+    let mut out = label.as_bytes().to_vec();
+    for (i, p) in parts.iter().enumerate() {
+        out.push(i as u8);
+        out.extend_from_slice(p);
+    }
+    out
+}
+
+fn shake_many(label: &str, parts: &[&[u8]], len: usize) -> Vec<u8> {
+    let mut h = Shake256::default();
+    h.update(label.as_bytes());
+    for (i, p) in parts.iter().enumerate() {
+        h.update(&[i as u8]);
+        h.update(p);
+    }
+    let mut out = vec![0u8; len];
+    h.finalize_xof().read(&mut out);
+    out
+}
+
+fn shake_xof(data: &[u8]) -> Shake256Reader {
+    let mut h = Shake256::default();
+    h.update(data);
+    h.finalize_xof()
+}
+
+/// SHAKE256 with 384-bit digests; the default, with a 128-bit post-quantum collision margin.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Shake256_384;
+
+impl MerkleHasher for Shake256_384 {
+    fn digest_len(&self) -> usize { 48 }
+    fn hash_many(&self, label: &str, parts: &[&[u8]]) -> Vec<u8> { shake_many(label, parts, 48) }
+}
+
+impl TranscriptHash for Shake256_384 {
+    type Reader = Shake256Reader;
+    fn state_len(&self) -> usize { 48 }
+    fn xof(&self, data: &[u8]) -> Shake256Reader { shake_xof(data) }
+}
+
+/// SHAKE256 with 256-bit digests, the 384-bit ones truncated: smaller proofs, 128-bit
+/// classical collision resistance.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Shake256_256;
+
+impl MerkleHasher for Shake256_256 {
+    fn digest_len(&self) -> usize { 32 }
+    fn hash_many(&self, label: &str, parts: &[&[u8]]) -> Vec<u8> { shake_many(label, parts, 32) }
+}
+
+impl TranscriptHash for Shake256_256 {
+    type Reader = Shake256Reader;
+    fn state_len(&self) -> usize { 32 }
+    fn xof(&self, data: &[u8]) -> Shake256Reader { shake_xof(data) }
+}
+
+/// BLAKE3 with 256-bit digests, the fastest native backend.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Blake3;
+
+/// BLAKE3 output stream.
+pub struct Blake3Reader(blake3::OutputReader);
+
+impl XofReader for Blake3Reader {
+    fn read(&mut self, buffer: &mut [u8]) { self.0.fill(buffer) }
+}
+
+impl MerkleHasher for Blake3 {
+    fn digest_len(&self) -> usize { 32 }
+    fn hash_many(&self, label: &str, parts: &[&[u8]]) -> Vec<u8> { blake3::hash(&framed(label, parts)).as_bytes().to_vec() }
+}
+
+impl TranscriptHash for Blake3 {
+    type Reader = Blake3Reader;
+    fn state_len(&self) -> usize { 32 }
+    fn xof(&self, data: &[u8]) -> Blake3Reader {
+        let mut h = blake3::Hasher::new();
+        h.update(data);
+        Blake3Reader(h.finalize_xof())
+    }
+}
+
+/// Poseidon2 over Goldilocks: byte strings are packed into field elements, and Merkle parents of
+/// canonical digests use the 2-to-1 compression, so trees are cheap to open inside an AIR.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Poseidon2;
+
+impl XofReader for poseidon2::SpongeReader {
+    fn read(&mut self, buffer: &mut [u8]) { poseidon2::SpongeReader::read(self, buffer) }
+}
+
+impl MerkleHasher for Poseidon2 {
+    fn digest_len(&self) -> usize { 8 * poseidon2::DIGEST_ELEMS }
+    fn hash_many(&self, label: &str, parts: &[&[u8]]) -> Vec<u8> {
+        poseidon2::digest_to_bytes(&poseidon2::hash_bytes(&framed(label, parts)))
+    }
+    fn hash_node(&self, left: &[u8], right: &[u8]) -> Vec<u8> {
+        match (poseidon2::digest_from_bytes(left), poseidon2::digest_from_bytes(right)) {
+            (Some(l), Some(r)) => poseidon2::digest_to_bytes(&poseidon2::compress(&l, &r)),
+            _ => self.hash_many(DOM_MERKLE_NODE, &[left, right]),
+        }
+    }
+    fn is_digest(&self, bytes: &[u8]) -> bool { poseidon2::digest_from_bytes(bytes).is_some() }
+}
+
+impl TranscriptHash for Poseidon2 {
+    type Reader = poseidon2::SpongeReader;
+    fn state_len(&self) -> usize { 8 * poseidon2::DIGEST_ELEMS }
+    fn xof(&self, data: &[u8]) -> poseidon2::SpongeReader {
+        poseidon2::SpongeReader::new(&poseidon2::bytes_to_elements(data))
+    }
+}
+
+/// Hash backend selected for a proof.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HashFunction {
+    #[default]
+    Shake256_384,
+    Shake256_256,
+    Blake3,
+    Poseidon2,
+}
+
+impl HashFunction {
+    pub const ALL: [HashFunction; 4] = [Self::Shake256_384, Self::Shake256_256, Self::Blake3, Self::Poseidon2];
+
+    /// Stable name, as accepted by [`FromStr`].
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Shake256_384 => "shake256-384",
+            Self::Shake256_256 => "shake256-256",
+            Self::Blake3 => "blake3",
+            Self::Poseidon2 => "poseidon2",
+        }
+    }
+}
+
+impl fmt::Display for HashFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { f.write_str(self.name()) }
+}
+
+impl FromStr for HashFunction {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, String> {
+        Self::ALL.into_iter().find(|h| h.name() == s).ok_or_else(|| format!("unknown hash function {s:?}"))
+    }
+}
+
+/// Output stream of a [`HashFunction`]. Readers live for one squeeze, so the SHAKE state is
+/// kept inline rather than boxed.
+#[allow(clippy::large_enum_variant)]
+pub enum HashReader {
+    Shake(Shake256Reader),
+    Blake3(Blake3Reader),
+    Poseidon2(poseidon2::SpongeReader),
+}
+
+impl XofReader for HashReader {
+    fn read(&mut self, buffer: &mut [u8]) {
+        match self {
+            Self::Shake(r) => r.read(buffer),
+            Self::Blake3(r) => r.read(buffer),
+            Self::Poseidon2(r) => XofReader::read(r, buffer),
+        }
+    }
+}
+
+impl MerkleHasher for HashFunction {
+    fn digest_len(&self) -> usize {
+        match self {
+            Self::Shake256_384 => Shake256_384.digest_len(),
+            Self::Shake256_256 => Shake256_256.digest_len(),
+            Self::Blake3 => Blake3.digest_len(),
+            Self::Poseidon2 => Poseidon2.digest_len(),
+        }
+    }
+    fn hash_many(&self, label: &str, parts: &[&[u8]]) -> Vec<u8> {
+        match self {
+            Self::Shake256_384 => Shake256_384.hash_many(label, parts),
+            Self::Shake256_256 => Shake256_256.hash_many(label, parts),
+            Self::Blake3 => Blake3.hash_many(label, parts),
+            Self::Poseidon2 => Poseidon2.hash_many(label, parts),
+        }
+    }
+    fn hash_node(&self, left: &[u8], right: &[u8]) -> Vec<u8> {
+        match self {
+            Self::Shake256_384 => Shake256_384.hash_node(left, right),
+            Self::Shake256_256 => Shake256_256.hash_node(left, right),
+            Self::Blake3 => Blake3.hash_node(left, right),
+            Self::Poseidon2 => Poseidon2.hash_node(left, right),
+        }
+    }
+    fn is_digest(&self, bytes: &[u8]) -> bool {
+        match self {
+            Self::Poseidon2 => Poseidon2.is_digest(bytes),
+            _ => bytes.len() == self.digest_len(),
+        }
+    }
+}
+
+impl TranscriptHash for HashFunction {
+    type Reader = HashReader;
+    fn state_len(&self) -> usize {
+        match self {
+            Self::Shake256_384 => Shake256_384.state_len(),
+            Self::Shake256_256 => Shake256_256.state_len(),
+            Self::Blake3 => Blake3.state_len(),
+            Self::Poseidon2 => Poseidon2.state_len(),
+        }
+    }
+    fn xof(&self, data: &[u8]) -> HashReader {
+        match self {
+            Self::Shake256_384 => HashReader::Shake(Shake256_384.xof(data)),
+            Self::Shake256_256 => HashReader::Shake(Shake256_256.xof(data)),
+            Self::Blake3 => HashReader::Blake3(Blake3.xof(data)),
+            Self::Poseidon2 => HashReader::Poseidon2(Poseidon2.xof(data)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{h2, h_many, DIGEST_LEN};
+
+    #[test]
+    fn default_backend_matches_free_functions() {
+        let h = HashFunction::default();
+        assert_eq!(h.digest_len(), DIGEST_LEN);
+        assert_eq!(h.hash_many("d", &[b"a", b"bc"]), h_many("d", &[b"a", b"bc"]).to_vec());
+        assert_eq!(h.hash_node(b"left", b"right"), h2(DOM_MERKLE_NODE, b"left", b"right").to_vec());
+        let mut out = [0u8; DIGEST_LEN];
+        h.xof(b"data").read(&mut out);
+        assert_eq!(out, crate::shake256_384(b"data"));
+    }
+
+    #[test]
+    fn backends_separate_and_size_digests() {
+        for h in HashFunction::ALL {
+            assert_eq!(h.name().parse::<HashFunction>(), Ok(h));
+            let a = h.hash_many("d", &[b"a"]);
+            assert_eq!(a.len(), h.digest_len());
+            assert!(h.is_digest(&a));
+            assert_ne!(a, h.hash_many("e", &[b"a"]));
+            assert_ne!(a, h.hash_many("d", &[b"a", b""]));
+            let node = h.hash_node(&a, &a);
+            assert_eq!(node.len(), h.digest_len());
+            assert!(!h.is_digest(&node[1..]));
+            // The stream is deterministic and longer than one state
+            let (mut x, mut y) = ([0u8; 100], [0u8; 100]);
+            h.xof(b"seed").read(&mut x);
+            h.xof(b"seed").read(&mut y[..h.state_len()]);
+            h.xof(b"seed").read(&mut y[..h.state_len()]);
+            let mut r = h.xof(b"seed");
+            r.read(&mut y[..h.state_len()]);
+            r.read(&mut y[h.state_len()..]);
+            assert_eq!(x, y);
+        }
+        // Both SHAKE widths read the same stream
+        assert!(Shake256_384.hash_many("d", &[b"a"]).starts_with(&Shake256_256.hash_many("d", &[b"a"])));
+        assert_ne!(Blake3.hash_many("d", &[b"a"]), Poseidon2.hash_many("d", &[b"a"]));
+        assert!("sha256".parse::<HashFunction>().is_err());
+    }
+
+    #[test]
+    fn poseidon2_nodes_compress_digests() {
+        let h = Poseidon2;
+        let (l, r) = (h.hash_many("leaf", &[b"l"]), h.hash_many("leaf", &[b"r"]));
+        let expected = poseidon2::compress(&poseidon2::digest_from_bytes(&l).unwrap(), &poseidon2::digest_from_bytes(&r).unwrap());
+        assert_eq!(h.hash_node(&l, &r), poseidon2::digest_to_bytes(&expected));
+        // Non-canonical siblings are not digests and take the byte path
+        let bad = [0xffu8; 32];
+        assert!(!h.is_digest(&bad));
+        assert_eq!(h.hash_node(&l, &bad), h.hash_many(DOM_MERKLE_NODE, &[&l, &bad]));
+    }
+}
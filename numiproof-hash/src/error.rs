@@ -0,0 +1,55 @@
+// File: numiproof-hash/src/error.rs
+//! Verification failures shared by the Merkle, FRI and proof verifiers.
+use std::fmt;
+
+/// Reason a proof or an opening was rejected.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VerifyError {
+    /// The proof format version is not supported by this verifier.
+    BadVersion { found: u8 },
+    /// The proof was produced for a different AIR than the one it is verified against.
+    UnknownAir { found: String },
+    /// An authentication path does not lead to its committed root, or opens the wrong leaf.
+    /// `query` is the query number for proof-level checks and the leaf index for a standalone
+    /// opening.
+    MerklePathMismatch { query: usize },
+    /// The AIR relation does not hold at the out-of-domain point. The check is a random
+    /// linear combination over the whole trace, so the offending row cannot be recovered; `z`
+    /// is the point checked, as canonical coefficients over the trace field.
+    ConstraintFailure { z: Vec<u64> },
+    /// The DEEP composition recomputed from the openings of query `query` differs from the
+    /// value the first FRI layer commits to.
+    DeepMismatch { query: usize },
+    /// A folded value disagrees with the next layer, or with the final polynomial when `round`
+    /// equals the number of committed layers.
+    FriFoldMismatch { round: usize },
+    /// The proof-of-work nonce does not meet the declared difficulty, or the difficulty is below
+    /// the verifier's minimum.
+    InvalidProofOfWork,
+    /// A soundness parameter of the proof (`queries` or `blowup`) is below the verifier's
+    /// minimum.
+    WeakParameters(&'static str),
+    /// A field has the wrong length, is out of range or fails to decode.
+    MalformedEncoding(&'static str),
+    /// The proof digest does not bind the committed statement.
+    DigestMismatch,
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BadVersion { found } => write!(f, "unsupported proof version {found}"),
+            Self::UnknownAir { found } => write!(f, "proof is for unknown AIR {found:?}"),
+            Self::MerklePathMismatch { query } => write!(f, "Merkle path mismatch at query {query}"),
+            Self::ConstraintFailure { z } => write!(f, "AIR constraints fail at the out-of-domain point z = {z:?}"),
+            Self::DeepMismatch { query } => write!(f, "DEEP composition mismatch at query {query}"),
+            Self::FriFoldMismatch { round } => write!(f, "FRI fold mismatch in round {round}"),
+            Self::InvalidProofOfWork => write!(f, "proof-of-work nonce below the required difficulty"),
+            Self::WeakParameters(what) => write!(f, "proof {what} below the verifier's minimum"),
+            Self::MalformedEncoding(what) => write!(f, "malformed proof: {what}"),
+            Self::DigestMismatch => write!(f, "proof digest mismatch"),
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
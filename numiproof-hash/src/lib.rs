@@ -1,13 +1,25 @@
 // File: numiproof-hash/src/lib.rs
 use rand::{rngs::StdRng, SeedableRng};
+use rayon::prelude::*;
 use sha3::{digest::{ExtendableOutput, Update, XofReader}, Shake256};
 use serde::{Serialize, Deserialize};
+use numiproof_field::{ExtensionField, Fp, PrimeField};
 
+mod backend;
+mod error;
+pub mod poseidon2;
+pub use backend::{Blake3, HashFunction, HashReader, MerkleHasher, Poseidon2, Shake256_256, Shake256_384, TranscriptHash};
+pub use error::VerifyError;
+
+/// Largest proof-of-work difficulty [`Transcript::grind`] accepts; beyond it the search is
+/// impractical (and above 64 bits it cannot succeed).
+pub const MAX_GRINDING_BITS: u32 = 32;
 pub const DIGEST_LEN: usize = 48; // 384-bit output
 
 // Domain separation labels for hashed constructs in this workspace
 pub const DOM_ROW: &str = "row";
 pub const DOM_MERKLE_NODE: &str = "merkle.node";
+pub const DOM_MERKLE_SALT: &str = "merkle.salt";
 pub const DOM_FRI_LEAF: &str = "fri.leaf";
 pub const DOM_PROOF_DIGEST: &str = "proof.digest";
 pub const DOM_ACCUMULATOR: &str = "accumulator";
@@ -50,14 +62,22 @@ pub fn h_many(label: &str, parts: &[&[u8]]) -> [u8; DIGEST_LEN] {
     out
 }
 
+/// Duplex-sponge Fiat–Shamir transcript. Every absorb and every squeeze ratchets the state, so
+/// two challenges drawn in sequence are independent even when nothing is absorbed in between.
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Transcript {
     state: Vec<u8>,
+    /// Number of squeezes so far; bound into every squeeze for domain separation.
+    counter: u64,
+    hash: HashFunction,
 }
 impl Transcript {
-    pub fn new(domain: &str) -> Self {
-        Self { state: domain.as_bytes().to_vec() }
+    /// Transcript over the default SHAKE256-384 backend.
+    pub fn new(domain: &str) -> Self { Self::with_hash(domain, HashFunction::default()) }
+    pub fn with_hash(domain: &str, hash: HashFunction) -> Self {
+        Self { state: domain.as_bytes().to_vec(), counter: 0, hash }
     }
+    pub fn hash(&self) -> HashFunction { self.hash }
     pub fn absorb(&mut self, label: &str, data: &[u8]) {
         let mut buf = Vec::with_capacity(self.state.len()+1+label.len()+data.len());
         buf.extend_from_slice(&self.state);
@@ -65,26 +85,134 @@ impl Transcript {
         buf.extend_from_slice(label.as_bytes());
         buf.push(0);
         buf.extend_from_slice(data);
-        self.state = shake256_384(&buf).to_vec();
+        let mut next_state = vec![0u8; self.hash.state_len()];
+        self.hash.xof(&buf).read(&mut next_state);
+        self.state = next_state;
+    }
+    /// Start a squeeze under `label`. The first `state_len` bytes of the XOF stream replace the
+    /// state and the rest of the stream is handed to the caller, so outputs never reveal the
+    /// state they were derived from.
+    fn squeeze(&mut self, label: &str) -> HashReader {
+        let mut buf = Vec::with_capacity(self.state.len() + 10 + label.len());
+        buf.extend_from_slice(&self.state);
+        buf.push(0xFE);
+        buf.extend_from_slice(label.as_bytes());
+        buf.push(0);
+        buf.extend_from_slice(&self.counter.to_le_bytes());
+        let mut xof = self.hash.xof(&buf);
+        let mut next_state = vec![0u8; self.hash.state_len()];
+        xof.read(&mut next_state);
+        self.state = next_state;
+        self.counter += 1;
+        xof
     }
-    pub fn challenge_bytes(&self, out_len: usize) -> Vec<u8> {
-        let mut hasher = Shake256::default();
-        hasher.update(&self.state);
-        let mut xof = hasher.finalize_xof();
+    /// Squeeze `out_len` bytes under `label`.
+    pub fn challenge_bytes(&mut self, label: &str, out_len: usize) -> Vec<u8> {
         let mut out = vec![0u8; out_len];
-        xof.read(&mut out);
+        self.squeeze(label).read(&mut out);
         out
     }
-    pub fn challenge_u64(&self) -> u64 {
-        let b = self.challenge_bytes(8);
-        u64::from_le_bytes(b.try_into().unwrap())
+    pub fn challenge_u64(&mut self, label: &str) -> u64 {
+        read_u64(&mut self.squeeze(label))
+    }
+    /// Squeeze a uniformly distributed Goldilocks element.
+    pub fn challenge_fp(&mut self, label: &str) -> Fp {
+        sample_fp(&mut self.squeeze(label))
+    }
+    /// Squeeze `n` uniformly distributed Goldilocks elements from one ratchet step.
+    pub fn challenge_fps(&mut self, label: &str, n: usize) -> Vec<Fp> {
+        self.challenge_field_elems(label, n)
+    }
+    /// Squeeze `n` uniformly distributed elements of the prime field `F` from one ratchet step.
+    pub fn challenge_field_elems<F: PrimeField>(&mut self, label: &str, n: usize) -> Vec<F> {
+        let mut xof = self.squeeze(label);
+        (0..n).map(|_| sample_fp(&mut xof)).collect()
+    }
+    /// Squeeze a uniformly distributed element of the extension `E` of `F`, one base coordinate
+    /// at a time.
+    pub fn challenge_ext<F: PrimeField, E: ExtensionField<F>>(&mut self, label: &str) -> E {
+        E::from_base_slice(&self.challenge_field_elems(label, E::DEGREE))
+    }
+    /// Squeeze `n` uniformly distributed elements of `E` from one ratchet step.
+    pub fn challenge_exts<F: PrimeField, E: ExtensionField<F>>(&mut self, label: &str, n: usize) -> Vec<E> {
+        let mut xof = self.squeeze(label);
+        (0..n)
+            .map(|_| E::from_base_slice(&(0..E::DEGREE).map(|_| sample_fp(&mut xof)).collect::<Vec<_>>()))
+            .collect()
+    }
+    /// Squeeze `n` uniformly distributed indices into `0..domain`.
+    pub fn challenge_indices(&mut self, label: &str, n: usize, domain: usize) -> Vec<usize> {
+        assert!(domain > 0);
+        let mut xof = self.squeeze(label);
+        (0..n).map(|_| sample_index(&mut xof, domain)).collect()
+    }
+    /// Proof-of-work: the smallest nonce whose [`pow_word`](Self::pow_word) has `bits` leading
+    /// zeros, searched in parallel and then absorbed. Costs about `2^bits` hashes; panics above
+    /// [`MAX_GRINDING_BITS`].
+    pub fn grind(&mut self, bits: u32) -> u64 {
+        assert!(bits <= MAX_GRINDING_BITS, "grinding_bits above MAX_GRINDING_BITS");
+        let nonce = (0..u64::MAX)
+            .into_par_iter()
+            .find_first(|&n| self.pow_word(bits, n).leading_zeros() >= bits)
+            .expect("a nonce exists");
+        self.absorb("pow.nonce", &nonce.to_le_bytes());
+        nonce
+    }
+    /// Check a nonce found by [`grind`](Self::grind) and absorb it.
+    pub fn check_pow(&mut self, bits: u32, nonce: u64) -> bool {
+        let ok = self.pow_word(bits, nonce).leading_zeros() >= bits;
+        self.absorb("pow.nonce", &nonce.to_le_bytes());
+        ok
+    }
+    /// First 8 bytes (big-endian) of the hash of the state, the difficulty and `nonce`.
+    fn pow_word(&self, bits: u32, nonce: u64) -> u64 {
+        let mut buf = Vec::with_capacity(self.state.len() + 13);
+        buf.extend_from_slice(&self.state);
+        buf.push(0xFD);
+        buf.extend_from_slice(&bits.to_le_bytes());
+        buf.extend_from_slice(&nonce.to_le_bytes());
+        let mut word = [0u8; 8];
+        self.hash.xof(&buf).read(&mut word);
+        u64::from_be_bytes(word)
     }
-    pub fn rng(&self) -> StdRng {
-        let seed = self.challenge_bytes(32);
+    pub fn rng(&mut self, label: &str) -> StdRng {
+        let seed = self.challenge_bytes(label, 32);
         StdRng::from_seed(seed.as_slice().try_into().unwrap())
     }
 }
 
+#[inline]
+fn read_u64<R: XofReader>(xof: &mut R) -> u64 {
+    let mut b = [0u8; 8];
+    xof.read(&mut b);
+    u64::from_le_bytes(b)
+}
+
+/// Rejection-sample a canonical field element from 64-bit little-endian words of the stream,
+/// masked to the bit length of the modulus. Values >= MODULUS are discarded, so every element of
+/// the field is equally likely.
+fn sample_fp<F: PrimeField, R: XofReader>(xof: &mut R) -> F {
+    let mask = u64::MAX >> F::MODULUS.leading_zeros();
+    loop {
+        let v = read_u64(xof) & mask;
+        if v < F::MODULUS { return F::from_u64(v); }
+    }
+}
+
+/// Rejection-sample an index in `0..domain`. Powers of two are masked directly; otherwise words
+/// in the incomplete top bucket `[2^64 - (2^64 mod domain), 2^64)` are discarded.
+fn sample_index<R: XofReader>(xof: &mut R, domain: usize) -> usize {
+    let d = domain as u64;
+    if d.is_power_of_two() {
+        return (read_u64(xof) & (d - 1)) as usize;
+    }
+    let rem = (u64::MAX % d + 1) % d;
+    loop {
+        let v = read_u64(xof);
+        if v <= u64::MAX - rem { return (v % d) as usize; }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -114,8 +242,140 @@ mod tests {
         let mut t2 = Transcript::new("ns");
         t1.absorb("k", b"v");
         t2.absorb("k", b"v");
-        assert_eq!(t1.challenge_bytes(16), t2.challenge_bytes(16));
+        assert_eq!(t1.challenge_bytes("c", 16), t2.challenge_bytes("c", 16));
         t1.absorb("k", b"v2");
-        assert_ne!(t1.challenge_bytes(16), t2.challenge_bytes(16));
+        t2.absorb("k", b"v");
+        assert_ne!(t1.challenge_bytes("c", 16), t2.challenge_bytes("c", 16));
+    }
+
+    #[test]
+    fn transcript_squeezes_ratchet() {
+        let mut t = Transcript::new("ns");
+        t.absorb("k", b"v");
+        let a = t.challenge_fp("alpha");
+        let b = t.challenge_fp("alpha");
+        assert_ne!(a, b);
+        let fps = t.challenge_fps("alpha", 4);
+        assert_eq!(fps.len(), 4);
+        assert!(fps.windows(2).all(|w| w[0] != w[1]));
+        // Same sequence of operations replays identically
+        let mut r = Transcript::new("ns");
+        r.absorb("k", b"v");
+        assert_eq!(r.challenge_fp("alpha"), a);
+        assert_eq!(r.challenge_fp("alpha"), b);
+        assert_eq!(r.challenge_fps("alpha", 4), fps);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn extension_challenges_match_base_draws() {
+        let mut t = Transcript::new("ns");
+        let mut r = t.clone();
+        assert_eq!(t.challenge_ext::<Fp, Fp>("z"), r.challenge_fp("z"));
+        let z: numiproof_field::Fp3 = t.challenge_ext("z");
+        assert_eq!(z.base_coeffs(), &r.challenge_fps("z", 3)[..]);
+        let zs: Vec<numiproof_field::Fp2> = t.challenge_exts("g", 3);
+        assert_eq!(zs.iter().flat_map(|z| z.0).collect::<Vec<_>>(), r.challenge_fps("g", 6));
+    }
+
+    #[test]
+    fn transcript_backends_diverge() {
+        for h in HashFunction::ALL {
+            let mut t = Transcript::with_hash("ns", h);
+            let mut r = Transcript::with_hash("ns", h);
+            assert_eq!(t.hash(), h);
+            t.absorb("k", b"v");
+            r.absorb("k", b"v");
+            assert_eq!(t.challenge_fps("a", 3), r.challenge_fps("a", 3));
+            assert!(t.challenge_indices("q", 16, 1000).iter().all(|&i| i < 1000));
+        }
+        let draws: Vec<Fp> = HashFunction::ALL
+            .iter()
+            .map(|&h| {
+                let mut t = Transcript::with_hash("ns", h);
+                t.absorb("k", b"v");
+                t.challenge_fp("a")
+            })
+            .collect();
+        assert!(draws.iter().enumerate().all(|(i, d)| !draws[..i].contains(d)));
+    }
+
+    #[test]
+    fn grinding_finds_and_checks_nonces() {
+        for h in [HashFunction::Shake256_384, HashFunction::Poseidon2] {
+            let mut t = Transcript::with_hash("ns", h);
+            t.absorb("k", b"v");
+            let mut v = t.clone();
+            let nonce = t.grind(8);
+            assert!(v.clone().check_pow(8, nonce));
+            // Smallest such nonce, and a wrong one fails
+            assert!((0..nonce).all(|n| v.pow_word(8, n).leading_zeros() < 8));
+            let bad = (nonce + 1..).find(|&n| v.pow_word(8, n).leading_zeros() < 8).unwrap();
+            assert!(!v.clone().check_pow(8, bad));
+            // Checking absorbs the nonce exactly as grinding does
+            assert!(v.check_pow(8, nonce));
+            assert_eq!(t.challenge_u64("q"), v.challenge_u64("q"));
+        }
+        let mut t = Transcript::new("ns");
+        assert_eq!(t.grind(0), 0);
+    }
+
+    #[test]
+    fn transcript_label_separates_challenges() {
+        let mut t1 = Transcript::new("ns");
+        let mut t2 = Transcript::new("ns");
+        assert_ne!(t1.challenge_bytes("a", 16), t2.challenge_bytes("b", 16));
+        let idx = t1.challenge_indices("q", 64, 10);
+        assert!(idx.iter().all(|&i| i < 10));
+    }
+
+    /// Replays a fixed byte string as an XOF stream.
+    struct FixedReader(Vec<u8>, usize);
+    impl XofReader for FixedReader {
+        fn read(&mut self, buffer: &mut [u8]) {
+            buffer.copy_from_slice(&self.0[self.1..self.1 + buffer.len()]);
+            self.1 += buffer.len();
+        }
+    }
+    fn words(ws: &[u64]) -> FixedReader {
+        FixedReader(ws.iter().flat_map(|w| w.to_le_bytes()).collect(), 0)
+    }
+
+    #[test]
+    fn sample_fp_rejects_non_canonical_words() {
+        use numiproof_field::{BabyBear, MODULUS};
+        // MODULUS and u64::MAX are out of range and must be skipped rather than reduced
+        let mut r = words(&[MODULUS, u64::MAX, MODULUS - 1]);
+        assert_eq!(sample_fp::<Fp, _>(&mut r), Fp(MODULUS - 1));
+        let mut r = words(&[0, 5]);
+        assert_eq!(sample_fp::<Fp, _>(&mut r), Fp(0));
+        // Smaller fields mask each word to the modulus' bit length before rejecting
+        let p = BabyBear::MODULUS;
+        let mut r = words(&[p, (1 << 31) - 1, (7 << 32) | (p - 1)]);
+        assert_eq!(sample_fp::<BabyBear, _>(&mut r), BabyBear((p - 1) as u32));
+    }
+
+    #[test]
+    fn sample_index_rejects_top_bucket() {
+        // 2^64 mod 3 == 1, so only u64::MAX is in the incomplete bucket
+        let mut r = words(&[u64::MAX, u64::MAX - 1]);
+        assert_eq!(sample_index(&mut r, 3), ((u64::MAX - 1) % 3) as usize);
+        // Powers of two never reject
+        let mut r = words(&[u64::MAX]);
+        assert_eq!(sample_index(&mut r, 16), 15);
+    }
+
+    // Expected values computed independently with Python's hashlib.shake_256 following the
+    // same absorb/squeeze framing.
+    const KAT_FP: u64 = 10376838494669678358;
+    const KAT_FPS: [u64; 2] = [16432675167930989298, 8292021487511051421];
+    const KAT_IDX: [usize; 4] = [355, 581, 553, 948];
+
+    #[test]
+    fn transcript_known_answers() {
+        let mut t = Transcript::new("numiproof.kat");
+        t.absorb("k", b"v");
+        assert_eq!(t.challenge_fp("alpha"), Fp(KAT_FP));
+        assert_eq!(t.challenge_fps("alphas", 2), vec![Fp(KAT_FPS[0]), Fp(KAT_FPS[1])]);
+        assert_eq!(t.challenge_indices("query", 4, 1000), KAT_IDX.to_vec());
+    }
+}
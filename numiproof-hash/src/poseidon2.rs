@@ -0,0 +1,387 @@
+// File: numiproof-hash/src/poseidon2.rs
+//! Poseidon2 over Goldilocks with width 8 and `x^7` S-box (Grassi, Khovratovich, Schofnegger,
+//! 2023), following the reference instance: 8 full and 22 partial rounds, round constants from
+//! the Grain LFSR of the Poseidon specification, external layer `circ(2·M4, M4)` and internal
+//! layer `J + diag(MATRIX_DIAG)` with `J` all ones. On top of the permutation sit a sponge of
+//! rate 4, with a byte interface and an output stream, and a 2-to-1 compression for Merkle trees.
+use std::sync::OnceLock;
+
+use numiproof_field::{ExtensionField, Fp};
+
+/// State size in field elements.
+pub const WIDTH: usize = 8;
+/// Elements absorbed per permutation.
+pub const RATE: usize = 4;
+/// Digest size in field elements (256 bits).
+pub const DIGEST_ELEMS: usize = 4;
+/// Full rounds, half before and half after the partial rounds.
+pub const ROUNDS_F: usize = 8;
+/// Partial rounds, with the S-box on the first element only.
+pub const ROUNDS_P: usize = 22;
+/// S-box exponent; the smallest `d` with `gcd(d, p - 1) = 1`.
+pub const SBOX_DEGREE: u64 = 7;
+
+/// Diagonal of the internal matrix minus the identity.
+pub const MATRIX_DIAG: [u64; WIDTH] = [
+    0xa98811a1fed4e3a5,
+    0x1cc48b54f377e2a0,
+    0xe40cd4f6c5609a26,
+    0x11de79ebca97a4a3,
+    0x9177c73d8b7e929c,
+    0x2a6fe8085797e791,
+    0x3de6e93329f8d5ad,
+    0x3f7af9125da962fe,
+];
+
+/// A Poseidon2 digest.
+pub type Digest = [Fp; DIGEST_ELEMS];
+
+/// Round constants of the permutation.
+pub struct RoundConstants {
+    /// One row per full round: the first `ROUNDS_F / 2` precede the partial rounds.
+    pub external: [[Fp; WIDTH]; ROUNDS_F],
+    /// One constant per partial round, added to the first element.
+    pub internal: [Fp; ROUNDS_P],
+}
+
+/// The constants of the reference instance, generated on first use.
+pub fn round_constants() -> &'static RoundConstants {
+    static RC: OnceLock<RoundConstants> = OnceLock::new();
+    RC.get_or_init(|| {
+        let mut grain = Grain::new(WIDTH, ROUNDS_F, ROUNDS_P);
+        let mut external = [[Fp::zero(); WIDTH]; ROUNDS_F];
+        let mut internal = [Fp::zero(); ROUNDS_P];
+        // Drawn in round order
+        for row in &mut external[..ROUNDS_F / 2] {
+            row.iter_mut().for_each(|c| *c = grain.next_fp());
+        }
+        internal.iter_mut().for_each(|c| *c = grain.next_fp());
+        for row in &mut external[ROUNDS_F / 2..] {
+            row.iter_mut().for_each(|c| *c = grain.next_fp());
+        }
+        RoundConstants { external, internal }
+    })
+}
+
+/// `x^7`.
+#[inline]
+pub fn sbox<E: ExtensionField>(x: E) -> E {
+    let x2 = x * x;
+    let x3 = x2 * x;
+    x3 * x2 * x2
+}
+
+/// Multiply a 4-element block by `M4 = [[5,7,1,3],[4,6,1,1],[1,3,5,7],[1,1,4,6]]` with 8 additions.
+#[inline]
+fn apply_m4<E: ExtensionField>(x: &mut [E]) {
+    let t0 = x[0] + x[1];
+    let t1 = x[2] + x[3];
+    let t2 = x[1] + x[1] + t1;
+    let t3 = x[3] + x[3] + t0;
+    let t1_4 = t1 + t1;
+    let t4 = t1_4 + t1_4 + t3;
+    let t0_4 = t0 + t0;
+    let t5 = t0_4 + t0_4 + t2;
+    let t6 = t3 + t5;
+    let t7 = t2 + t4;
+    x[0] = t6;
+    x[1] = t5;
+    x[2] = t7;
+    x[3] = t4;
+}
+
+/// The external (full-round) linear layer `circ(2·M4, M4)`. Generic over extensions so
+/// constraints can apply it at out-of-domain points.
+#[inline]
+pub fn external_linear_layer<E: ExtensionField>(state: &mut [E; WIDTH]) {
+    state.chunks_exact_mut(4).for_each(apply_m4);
+    let sums: [E; 4] = core::array::from_fn(|i| state[i] + state[i + 4]);
+    for (i, x) in state.iter_mut().enumerate() {
+        *x += sums[i % 4];
+    }
+}
+
+/// The internal (partial-round) linear layer: `x_i <- d_i·x_i + sum_j x_j`.
+#[inline]
+pub fn internal_linear_layer<E: ExtensionField>(state: &mut [E; WIDTH]) {
+    let sum = state.iter().fold(E::zero(), |acc, &x| acc + x);
+    for (x, &d) in state.iter_mut().zip(&MATRIX_DIAG) {
+        *x = *x * Fp(d) + sum;
+    }
+}
+
+/// One full round: constants, S-box on every element, external layer.
+#[inline]
+pub fn full_round<E: ExtensionField>(state: &mut [E; WIDTH], rc: &[Fp; WIDTH]) {
+    for (x, &c) in state.iter_mut().zip(rc) {
+        *x = sbox(*x + E::from(c));
+    }
+    external_linear_layer(state);
+}
+
+/// One partial round: constant and S-box on the first element, internal layer.
+#[inline]
+pub fn partial_round<E: ExtensionField>(state: &mut [E; WIDTH], rc: Fp) {
+    state[0] = sbox(state[0] + E::from(rc));
+    internal_linear_layer(state);
+}
+
+/// The Poseidon2 permutation, in place.
+pub fn permute(state: &mut [Fp; WIDTH]) {
+    let rc = round_constants();
+    external_linear_layer(state);
+    let (first, last) = rc.external.split_at(ROUNDS_F / 2);
+    first.iter().for_each(|r| full_round(state, r));
+    rc.internal.iter().for_each(|&c| partial_round(state, c));
+    last.iter().for_each(|r| full_round(state, r));
+}
+
+/// Sponge hash of a variable-length message: absorbed `RATE` elements at a time after
+/// `10*` padding (a one, then zeros up to a multiple of `RATE`), digest squeezed from the rate.
+pub fn hash_elements(input: &[Fp]) -> Digest {
+    let state = absorb(input);
+    core::array::from_fn(|i| state[i])
+}
+
+/// [`hash_elements`] of the bytes packed by [`bytes_to_elements`].
+pub fn hash_bytes(bytes: &[u8]) -> Digest { hash_elements(&bytes_to_elements(bytes)) }
+
+/// Sponge state after absorbing the padded `input`.
+fn absorb(input: &[Fp]) -> [Fp; WIDTH] {
+    let mut state = [Fp::zero(); WIDTH];
+    let mut padded = input.to_vec();
+    padded.push(Fp::one());
+    padded.resize(padded.len().next_multiple_of(RATE), Fp::zero());
+    for block in padded.chunks_exact(RATE) {
+        for (s, &m) in state.iter_mut().zip(block) {
+            *s += m;
+        }
+        permute(&mut state);
+    }
+    state
+}
+
+/// Injective packing of bytes into field elements: a `0x01` byte is appended and every 7 bytes
+/// (little-endian) make one element, always below the modulus.
+pub fn bytes_to_elements(bytes: &[u8]) -> Vec<Fp> {
+    let mut padded = bytes.to_vec();
+    padded.push(1);
+    padded
+        .chunks(7)
+        .map(|c| Fp(c.iter().rev().fold(0u64, |acc, &b| (acc << 8) | b as u64)))
+        .collect()
+}
+
+/// Canonical little-endian encoding of a digest, 8 bytes per element.
+pub fn digest_to_bytes(digest: &Digest) -> Vec<u8> {
+    digest.iter().flat_map(|x| x.to_bytes()).collect()
+}
+
+/// Decode [`digest_to_bytes`]; rejects wrong lengths and non-canonical elements.
+pub fn digest_from_bytes(bytes: &[u8]) -> Option<Digest> {
+    if bytes.len() != 8 * DIGEST_ELEMS { return None; }
+    let mut out = [Fp::zero(); DIGEST_ELEMS];
+    for (x, chunk) in out.iter_mut().zip(bytes.chunks_exact(8)) {
+        *x = Fp::try_from_bytes(chunk)?;
+    }
+    Some(out)
+}
+
+/// Output stream of the sponge: the rate elements (8 little-endian bytes each), then the rate
+/// again after every further permutation.
+#[derive(Clone)]
+pub struct SpongeReader {
+    state: [Fp; WIDTH],
+    block: [u8; 8 * RATE],
+    pos: usize,
+}
+
+impl SpongeReader {
+    /// Squeeze after absorbing the padded `input`.
+    pub fn new(input: &[Fp]) -> Self {
+        let mut r = SpongeReader { state: absorb(input), block: [0; 8 * RATE], pos: 0 };
+        r.fill_block();
+        r
+    }
+
+    fn fill_block(&mut self) {
+        for (dst, x) in self.block.chunks_exact_mut(8).zip(&self.state) {
+            dst.copy_from_slice(&x.to_bytes());
+        }
+        self.pos = 0;
+    }
+
+    /// Fill `out` with the next bytes of the stream.
+    pub fn read(&mut self, out: &mut [u8]) {
+        for b in out {
+            if self.pos == self.block.len() {
+                permute(&mut self.state);
+                self.fill_block();
+            }
+            *b = self.block[self.pos];
+            self.pos += 1;
+        }
+    }
+}
+
+/// 2-to-1 compression for Merkle nodes: the first `DIGEST_ELEMS` elements of `P(left || right)`.
+pub fn compress(left: &Digest, right: &Digest) -> Digest {
+    let mut state = [Fp::zero(); WIDTH];
+    state[..DIGEST_ELEMS].copy_from_slice(left);
+    state[DIGEST_ELEMS..].copy_from_slice(right);
+    permute(&mut state);
+    core::array::from_fn(|i| state[i])
+}
+
+/// The Grain LFSR of the Poseidon specification in self-shrinking mode, seeded with the
+/// instance parameters (prime field, `x^d` S-box, 64-bit elements).
+struct Grain {
+    bits: [bool; 80],
+}
+
+impl Grain {
+    fn new(width: usize, rounds_f: usize, rounds_p: usize) -> Self {
+        let mut bits = [true; 80];
+        let mut i = 0;
+        let mut push = |v: u64, n: usize| {
+            for k in (0..n).rev() {
+                bits[i] = (v >> k) & 1 == 1;
+                i += 1;
+            }
+        };
+        // field type 1 (prime), S-box type 0 (x^d), field size, width, rounds; then 30 ones
+        push(1, 2);
+        push(0, 4);
+        push(64, 12);
+        push(width as u64, 12);
+        push(rounds_f as u64, 10);
+        push(rounds_p as u64, 10);
+        let mut g = Grain { bits };
+        for _ in 0..160 {
+            g.step();
+        }
+        g
+    }
+
+    fn step(&mut self) -> bool {
+        let b = &self.bits;
+        let new = b[62] ^ b[51] ^ b[38] ^ b[23] ^ b[13] ^ b[0];
+        self.bits.copy_within(1.., 0);
+        self.bits[79] = new;
+        new
+    }
+
+    /// Output the second bit of each pair whose first bit is set.
+    fn next_bit(&mut self) -> bool {
+        loop {
+            if self.step() { return self.step(); }
+            self.step();
+        }
+    }
+
+    /// Rejection-sample a field element from 64 big-endian bits.
+    fn next_fp(&mut self) -> Fp {
+        loop {
+            let v = (0..64).fold(0u64, |acc, _| (acc << 1) | self.next_bit() as u64);
+            if let Some(x) = Fp::from_canonical_u64(v) { return x; }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fps<const N: usize>(xs: [u64; N]) -> [Fp; N] { xs.map(Fp) }
+
+    #[test]
+    fn round_constants_match_reference() {
+        // Leading entries of the reference instance's external and internal tables
+        let rc = round_constants();
+        assert_eq!(rc.external[0][0], Fp(0xdd5743e7f2a5a5d9));
+        assert_eq!(rc.internal[0], Fp(0x488897d85ff51f56));
+        assert!(rc.external.iter().flatten().chain(&rc.internal).all(|c| c.0 < numiproof_field::MODULUS));
+    }
+
+    #[test]
+    fn permutation_known_answer() {
+        // Reference test vector: the permutation of [0, 1, ..., 7]
+        let mut state: [Fp; WIDTH] = core::array::from_fn(|i| Fp(i as u64));
+        permute(&mut state);
+        assert_eq!(
+            state,
+            fps([
+                14266028122062624699,
+                5353147180106052723,
+                15203350112844181434,
+                17630919042639565165,
+                16601551015858213987,
+                10184091939013874068,
+                16774100645754596496,
+                12047415603622314780,
+            ])
+        );
+    }
+
+    #[test]
+    fn linear_layers_match_matrices() {
+        let x: [Fp; WIDTH] = core::array::from_fn(|i| Fp::new(0x1234_5678_9abc_def0u64.wrapping_mul(i as u64 + 1)));
+        let m4 = [[5u64, 7, 1, 3], [4, 6, 1, 1], [1, 3, 5, 7], [1, 1, 4, 6]];
+        let mut ext = x;
+        external_linear_layer(&mut ext);
+        for (r, out) in ext.iter().enumerate() {
+            // circ(2·M4, M4): the diagonal block is doubled
+            let expected = (0..WIDTH).fold(Fp::zero(), |acc, c| {
+                let k = if r / 4 == c / 4 { 2 } else { 1 };
+                acc + x[c] * Fp(k * m4[r % 4][c % 4])
+            });
+            assert_eq!(*out, expected);
+        }
+        let mut int = x;
+        internal_linear_layer(&mut int);
+        let sum = x.iter().fold(Fp::zero(), |acc, &v| acc + v);
+        for i in 0..WIDTH {
+            assert_eq!(int[i], x[i] * Fp(MATRIX_DIAG[i]) + sum);
+        }
+    }
+
+    #[test]
+    fn sponge_pads_and_separates() {
+        let empty = hash_elements(&[]);
+        let zero = hash_elements(&[Fp::zero()]);
+        let one = hash_elements(&[Fp::one()]);
+        assert_ne!(empty, zero);
+        assert_ne!(empty, one);
+        assert_ne!(zero, one);
+        // Messages that fill the rate exactly get a whole padding block
+        let four = [Fp(1), Fp(2), Fp(3), Fp(4)];
+        let mut padded = four.to_vec();
+        padded.push(Fp::one());
+        assert_ne!(hash_elements(&four), hash_elements(&padded));
+        assert_eq!(hash_elements(&four), hash_elements(&four));
+        let (a, b) = (hash_elements(&four[..2]), hash_elements(&four[2..]));
+        assert_ne!(compress(&a, &b), compress(&b, &a));
+    }
+
+    #[test]
+    fn byte_packing_and_stream() {
+        // Trailing zero bytes change the packing
+        assert_ne!(bytes_to_elements(b"ab"), bytes_to_elements(b"ab\0"));
+        assert_eq!(bytes_to_elements(&[]), vec![Fp(1)]);
+        assert_eq!(bytes_to_elements(&[0xff; 7]), vec![Fp((1 << 56) - 1), Fp(1)]);
+        // The stream starts with the digest and continues past the first block
+        let msg = bytes_to_elements(b"numiproof");
+        let mut r = SpongeReader::new(&msg);
+        let mut out = [0u8; 3 * 8 * RATE + 5];
+        r.read(&mut out[..7]);
+        r.read(&mut out[7..]);
+        assert_eq!(out[..32], digest_to_bytes(&hash_elements(&msg))[..]);
+        let mut state = absorb(&msg);
+        permute(&mut state);
+        assert_eq!(out[32..40], state[0].to_bytes());
+        let d = hash_bytes(b"numiproof");
+        assert_eq!(digest_from_bytes(&digest_to_bytes(&d)), Some(d));
+        assert_eq!(digest_from_bytes(&[0xff; 32]), None);
+        assert_eq!(digest_from_bytes(&[0; 31]), None);
+    }
+}
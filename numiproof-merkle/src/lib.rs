@@ -1,15 +1,22 @@
 // File: numiproof-merkle/src/lib.rs
-use numiproof_hash::{h2, DIGEST_LEN, DOM_MERKLE_NODE};
+use numiproof_hash::{MerkleHasher, VerifyError, DOM_MERKLE_SALT};
+use rand::RngCore;
 use rayon::prelude::*;
 
+/// Length of the random salt mixed into every leaf of a hiding tree.
+pub const SALT_LEN: usize = 32;
+
 #[derive(Clone, Debug)]
 pub struct MerkleTree {
     nodes: Vec<Vec<u8>>,
+    /// Per-leaf salts of a hiding tree; each is revealed only with an opening of its leaf
+    salts: Option<Vec<Vec<u8>>>,
 }
 impl MerkleTree {
-    pub fn build(leaves: &[Vec<u8>]) -> Self {
+    /// Build over `leaves`, which must be digests of `hasher`.
+    pub fn build<H: MerkleHasher>(hasher: &H, leaves: &[Vec<u8>]) -> Self {
         let n = leaves.len().next_power_of_two();
-        let mut nodes = vec![vec![0u8; DIGEST_LEN]; 2*n];
+        let mut nodes = vec![vec![0u8; hasher.digest_len()]; 2*n];
         // Fill leaves in parallel
         nodes[n..n+n].par_iter_mut().enumerate().for_each(|(i, slot)| {
             let val = if i < leaves.len() { &leaves[i] } else { &leaves[leaves.len()-1] };
@@ -18,9 +25,31 @@ impl MerkleTree {
         // Compute internal nodes; sequential upward pass is sufficient given SHAKE throughput
         for i in (1..n).rev() {
             // Small trees don't benefit; sequential is fine for upper levels
-            nodes[i] = h2(DOM_MERKLE_NODE, &nodes[i<<1], &nodes[i<<1|1]).to_vec();
+            nodes[i] = hasher.hash_node(&nodes[i<<1], &nodes[i<<1|1]);
         }
-        Self { nodes }
+        Self { nodes, salts: None }
+    }
+    /// Build a hiding tree: every leaf is hashed together with a fresh random salt, so the root
+    /// and sibling paths reveal nothing about low-entropy leaves.
+    pub fn build_salted<H: MerkleHasher, R: RngCore + ?Sized>(hasher: &H, leaves: &[Vec<u8>], rng: &mut R) -> Self {
+        let salts: Vec<Vec<u8>> = leaves
+            .iter()
+            .map(|_| {
+                let mut salt = vec![0u8; SALT_LEN];
+                rng.fill_bytes(&mut salt);
+                salt
+            })
+            .collect();
+        let salted: Vec<Vec<u8>> = leaves.par_iter().zip(&salts).map(|(l, s)| Self::salted_leaf(hasher, s, l)).collect();
+        Self { salts: Some(salts), ..Self::build(hasher, &salted) }
+    }
+    /// Salt of leaf `idx` in a hiding tree, to be sent along with its opening.
+    pub fn salt(&self, idx: usize) -> Option<Vec<u8>> {
+        let salts = self.salts.as_ref()?;
+        Some(salts[idx.min(salts.len() - 1)].clone())
+    }
+    pub fn salted_leaf<H: MerkleHasher>(hasher: &H, salt: &[u8], leaf: &[u8]) -> Vec<u8> {
+        hasher.hash_many(DOM_MERKLE_SALT, &[salt, leaf])
     }
     pub fn root(&self) -> Vec<u8> { self.nodes[1].clone() }
     pub fn open(&self, mut idx: usize) -> Vec<Vec<u8>> {
@@ -33,23 +62,47 @@ impl MerkleTree {
         }
         path
     }
-    pub fn verify(root: &[u8], mut idx: usize, leaf: &[u8], path: &[Vec<u8>]) -> bool {
+    /// Check that `path` leads from `leaf` at `idx` to `root`. Paths longer than the tree are
+    /// rejected, since the extra levels would address a leaf beyond its width, and so are
+    /// siblings that are not digests of `hasher`.
+    pub fn verify<H: MerkleHasher>(hasher: &H, root: &[u8], idx: usize, leaf: &[u8], path: &[Vec<u8>]) -> Result<(), VerifyError> {
+        let mismatch = VerifyError::MerklePathMismatch { query: idx };
+        if path.len() < usize::BITS as usize && idx >> path.len() != 0 {
+            return Err(mismatch);
+        }
+        if !path.iter().all(|sib| hasher.is_digest(sib)) {
+            return Err(mismatch);
+        }
         let mut h = leaf.to_vec();
+        let mut i = idx;
         for sib in path {
-            h = if idx % 2 == 0 {
-                h2(DOM_MERKLE_NODE, &h, sib).to_vec()
-            } else {
-                h2(DOM_MERKLE_NODE, sib, &h).to_vec()
-            };
-            idx >>= 1;
+            h = if i & 1 == 0 { hasher.hash_node(&h, sib) } else { hasher.hash_node(sib, &h) };
+            i >>= 1;
+        }
+        if h == root { Ok(()) } else { Err(mismatch) }
+    }
+    /// Verify an opening of a hiding tree given the leaf's revealed salt.
+    pub fn verify_salted<H: MerkleHasher>(
+        hasher: &H,
+        root: &[u8],
+        idx: usize,
+        leaf: &[u8],
+        salt: &[u8],
+        path: &[Vec<u8>],
+    ) -> Result<(), VerifyError> {
+        if salt.len() != SALT_LEN {
+            return Err(VerifyError::MalformedEncoding("leaf salt length"));
         }
-        h == root
+        Self::verify(hasher, root, idx, &Self::salted_leaf(hasher, salt, leaf), path)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use numiproof_hash::{HashFunction, DIGEST_LEN};
+
+    const H: HashFunction = HashFunction::Shake256_384;
 
     fn leaf(i: u8) -> Vec<u8> { vec![i; DIGEST_LEN] }
 
@@ -57,32 +110,83 @@ mod tests {
     fn merkle_inclusion_first_middle_last() {
         // Build tree with non-power-of-two leaves to test padding
         let leaves = vec![leaf(1), leaf(2), leaf(3), leaf(4), leaf(5)];
-        let mt = MerkleTree::build(&leaves);
+        let mt = MerkleTree::build(&H, &leaves);
         let root = mt.root();
 
         for (i, l) in leaves.iter().enumerate() {
             let path = mt.open(i);
-            assert!(MerkleTree::verify(&root, i, l, &path));
+            assert!(MerkleTree::verify(&H, &root, i, l, &path).is_ok());
         }
         // Check padded last index equals last real leaf in storage
         let n = leaves.len().next_power_of_two();
         let last_real = leaves.len() - 1;
         let path = mt.open(n - 1);
-        assert!(MerkleTree::verify(&root, n - 1, &leaves[last_real], &path));
+        assert!(MerkleTree::verify(&H, &root, n - 1, &leaves[last_real], &path).is_ok());
     }
 
     #[test]
     fn merkle_rejects_tampered_leaf_or_path() {
         let leaves = vec![leaf(9), leaf(8), leaf(7), leaf(6)];
-        let mt = MerkleTree::build(&leaves);
+        let mt = MerkleTree::build(&H, &leaves);
         let root = mt.root();
         let idx = 2;
         let mut path = mt.open(idx);
         // Tamper with leaf
         let bad_leaf = leaf(0);
-        assert!(!MerkleTree::verify(&root, idx, &bad_leaf, &path));
+        assert!(MerkleTree::verify(&H, &root, idx, &bad_leaf, &path).is_err());
         // Tamper with path
         path[0][0] ^= 1;
-        assert!(!MerkleTree::verify(&root, idx, &leaves[idx], &path));
+        assert_eq!(
+            MerkleTree::verify(&H, &root, idx, &leaves[idx], &path),
+            Err(VerifyError::MerklePathMismatch { query: idx })
+        );
+        // An index beyond the tree width cannot alias a real leaf
+        assert!(MerkleTree::verify(&H, &root, idx + 4, &leaves[idx], &mt.open(idx)).is_err());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn salted_tree_hides_and_binds() {
+        let mut rng = rand::thread_rng();
+        let leaves = vec![leaf(1), leaf(1), leaf(2)];
+        let mt = MerkleTree::build_salted(&H, &leaves, &mut rng);
+        let root = mt.root();
+        for (i, l) in leaves.iter().enumerate() {
+            let salt = mt.salt(i).unwrap();
+            assert!(MerkleTree::verify_salted(&H, &root, i, l, &salt, &mt.open(i)).is_ok());
+            // The unsalted leaf alone does not open
+            assert!(MerkleTree::verify(&H, &root, i, l, &mt.open(i)).is_err());
+        }
+        // Equal leaves get unrelated hashes, and rebuilding gives a fresh root
+        assert_ne!(mt.open(0)[0], MerkleTree::salted_leaf(&H, &mt.salt(0).unwrap(), &leaves[0]));
+        assert_ne!(MerkleTree::build_salted(&H, &leaves, &mut rng).root(), root);
+        assert!(MerkleTree::build(&H, &leaves).salt(0).is_none());
+        // Wrong or truncated salt is rejected
+        let mut salt = mt.salt(2).unwrap();
+        salt[0] ^= 1;
+        assert!(MerkleTree::verify_salted(&H, &root, 2, &leaves[2], &salt, &mt.open(2)).is_err());
+        assert_eq!(
+            MerkleTree::verify_salted(&H, &root, 2, &leaves[2], &salt[1..], &mt.open(2)),
+            Err(VerifyError::MalformedEncoding("leaf salt length"))
+        );
+    }
+
+    #[test]
+    fn every_backend_builds_and_verifies() {
+        for h in HashFunction::ALL {
+            let leaves: Vec<Vec<u8>> = (0..5u8).map(|i| h.hash_many("leaf", &[&[i]])).collect();
+            let mt = MerkleTree::build(&h, &leaves);
+            let root = mt.root();
+            assert_eq!(root.len(), h.digest_len());
+            for (i, l) in leaves.iter().enumerate() {
+                assert!(MerkleTree::verify(&h, &root, i, l, &mt.open(i)).is_ok());
+            }
+            // Another backend does not accept the same opening
+            let other = HashFunction::ALL[(h as usize + 1) % HashFunction::ALL.len()];
+            assert!(MerkleTree::verify(&other, &root, 1, &leaves[1], &mt.open(1)).is_err());
+            // Nor does a sibling of the wrong length
+            let mut path = mt.open(2);
+            path[0].push(0);
+            assert!(MerkleTree::verify(&h, &root, 2, &leaves[2], &path).is_err());
+        }
+    }
+}
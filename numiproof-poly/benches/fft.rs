@@ -0,0 +1,68 @@
+// File: numiproof-poly/benches/fft.rs
+//! The parallel radix-4 FFT against a serial textbook radix-2 one at 2^20 to 2^24 points, and
+//! `batch_lde` over a trace-sized set of columns.
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use numiproof_field::{bit_reverse, Fp, TwoAdicField};
+use numiproof_poly::{batch_lde, lde_from_evals, Radix2Domain};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Serial radix-2 DIT, one twiddle multiplication chain per block.
+fn fft_reference(a: &mut [Fp], root: Fp) {
+    let n = a.len();
+    let bits = n.trailing_zeros();
+    for i in 0..n {
+        let j = bit_reverse(i, bits);
+        if j > i { a.swap(i, j); }
+    }
+    let mut m = 2;
+    while m <= n {
+        let w_m = root.pow((n / m) as u128);
+        for k in (0..n).step_by(m) {
+            let mut w = Fp::one();
+            for j in 0..m / 2 {
+                let t = a[k + j + m / 2] * w;
+                let u = a[k + j];
+                a[k + j] = u + t;
+                a[k + j + m / 2] = u - t;
+                w *= w_m;
+            }
+        }
+        m <<= 1;
+    }
+}
+
+fn random(n: usize, seed: u64) -> Vec<Fp> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..n).map(|_| Fp::new(rng.gen())).collect()
+}
+
+fn bench_fft(c: &mut Criterion) {
+    let mut group = c.benchmark_group("fft");
+    group.sample_size(10);
+    for log_n in [20u32, 22, 24] {
+        let n = 1usize << log_n;
+        let input = random(n, log_n as u64);
+        let domain = Radix2Domain::<Fp>::new(n);
+        group.bench_with_input(BenchmarkId::new("parallel_radix4", log_n), &input, |b, input| {
+            b.iter_batched_ref(|| input.clone(), |a| domain.fft(a), BatchSize::LargeInput)
+        });
+        group.bench_with_input(BenchmarkId::new("serial_radix2", log_n), &input, |b, input| {
+            b.iter_batched_ref(|| input.clone(), |a| fft_reference(a, Fp::root_of_unity(log_n)), BatchSize::LargeInput)
+        });
+    }
+    group.finish();
+}
+
+fn bench_batch_lde(c: &mut Criterion) {
+    // 8 columns of 2^18 rows extended 4x, 2^20 points each
+    let cols: Vec<Vec<Fp>> = (0..8).map(|i| random(1 << 18, 100 + i)).collect();
+    let mut group = c.benchmark_group("lde_8x2^18_blowup4");
+    group.sample_size(10);
+    group.bench_function("batch_lde", |b| b.iter(|| batch_lde(&cols, 2)));
+    group.bench_function("per_column", |b| b.iter(|| cols.iter().map(|c| lde_from_evals(c, 2)).collect::<Vec<_>>()));
+    group.finish();
+}
+
+criterion_group!(benches, bench_fft, bench_batch_lde);
+criterion_main!(benches);
@@ -0,0 +1,293 @@
+// File: numiproof-poly/src/domain.rs
+//! Radix-2 evaluation domains `offset·<w>` with their twiddle tables computed once, so every
+//! transform over the same domain (one per trace column, say) reuses them.
+use core::ops::Mul;
+
+use numiproof_field::{batch_inverse, dot_product, scale_slice, ExtensionField, TwoAdicField};
+use rayon::prelude::*;
+
+use crate::fft::{fft_with_twiddles, stage_twiddles};
+
+/// The coset `offset·<w>` of the subgroup of order `size = 2^log_size`; `offset = 1` is the
+/// subgroup itself.
+#[derive(Clone, Debug)]
+pub struct Radix2Domain<F> {
+    log_size: u32,
+    generator: F,
+    offset: F,
+    /// Per-stage FFT twiddles of `w`; the last `size / 2` are `w^j` for `j < size / 2`
+    twiddles: Vec<F>,
+    /// The same for `w^-1`
+    inv_twiddles: Vec<F>,
+    size_inv: F,
+}
+
+impl<F: TwoAdicField> Radix2Domain<F> {
+    /// The subgroup of order `size`, a power of two.
+    pub fn new(size: usize) -> Self { Self::new_coset(size, F::one()) }
+
+    /// The coset `offset·<w>` of the subgroup of order `size`, a power of two.
+    pub fn new_coset(size: usize, offset: F) -> Self {
+        assert!(size.is_power_of_two(), "domain size must be a power of two");
+        let log_size = size.trailing_zeros();
+        let generator = F::root_of_unity(log_size);
+        Self {
+            log_size,
+            generator,
+            offset,
+            twiddles: stage_twiddles(generator, size),
+            inv_twiddles: stage_twiddles(generator.inv(), size),
+            size_inv: F::from_u64(size as u64).inv(),
+        }
+    }
+
+    pub fn size(&self) -> usize { 1 << self.log_size }
+    pub fn log_size(&self) -> u32 { self.log_size }
+    /// Generator `w` of the underlying subgroup.
+    pub fn generator(&self) -> F { self.generator }
+    pub fn offset(&self) -> F { self.offset }
+
+    /// `w^j` for `j < size / 2`.
+    fn half_powers(&self) -> &[F] { &self.twiddles[(self.size() / 2).saturating_sub(1)..] }
+
+    /// The `i`-th point, `offset·w^i` (with `i` taken modulo the size).
+    pub fn element(&self, i: usize) -> F {
+        let pows = self.half_powers();
+        let half = pows.len();
+        if half == 0 { return self.offset; }
+        let i = i & (self.size() - 1);
+        // w^(size/2) = -1
+        if i < half { self.offset * pows[i] } else { -(self.offset * pows[i - half]) }
+    }
+
+    /// Every point in order.
+    pub fn elements(&self) -> Vec<F> {
+        let mut out = self.half_powers().to_vec();
+        out.extend(self.half_powers().iter().map(|&t| -t));
+        if out.is_empty() { out.push(F::one()); }
+        if self.offset != F::one() { scale_slice(&mut out, self.offset); }
+        out
+    }
+
+    /// Evaluate coefficients (`a.len() == size`) on the subgroup `<w>`, in place.
+    pub fn fft<E: ExtensionField<F>>(&self, a: &mut [E]) {
+        assert_eq!(a.len(), self.size(), "length must match the domain");
+        fft_with_twiddles(a, &self.twiddles);
+    }
+
+    /// Interpolate evaluations on the subgroup `<w>` into coefficients, in place.
+    pub fn ifft<E: ExtensionField<F>>(&self, a: &mut [E]) {
+        assert_eq!(a.len(), self.size(), "length must match the domain");
+        fft_with_twiddles(a, &self.inv_twiddles);
+        scale_slice(a, self.size_inv);
+    }
+
+    /// Evaluate coefficients (`a.len() == size`) on the coset `offset·<w>`, in place.
+    pub fn coset_fft<E: ExtensionField<F>>(&self, a: &mut [E]) {
+        scale_by_powers(a, self.offset);
+        self.fft(a);
+    }
+
+    /// Interpolate evaluations on the coset `offset·<w>` into coefficients, in place.
+    pub fn coset_ifft<E: ExtensionField<F>>(&self, a: &mut [E]) {
+        self.ifft(a);
+        scale_by_powers(a, self.offset.inv());
+    }
+
+    /// Evaluations on the domain of the polynomial with `coeffs` (at most `size` of them).
+    pub fn evaluate<E: ExtensionField<F>>(&self, coeffs: &[E]) -> Vec<E> {
+        assert!(coeffs.len() <= self.size(), "more coefficients than domain points");
+        let mut a = coeffs.to_vec();
+        a.resize(self.size(), E::zero());
+        self.coset_fft(&mut a);
+        a
+    }
+
+    /// Coefficients of the interpolant of `evals`, given on the domain.
+    pub fn interpolate<E: ExtensionField<F>>(&self, evals: &[E]) -> Vec<E> {
+        let mut a = evals.to_vec();
+        self.coset_ifft(&mut a);
+        a
+    }
+
+    /// The vanishing polynomial `X^size - offset^size` of the domain at `x`.
+    pub fn evaluate_vanishing<E: ExtensionField<F>>(&self, x: E) -> E {
+        x.pow(self.size() as u128) - E::from(self.offset.pow(self.size() as u128))
+    }
+
+    /// Values at `x` of the Lagrange basis polynomials of the domain, so that
+    /// `f(x) = sum_i L_i(x)·f(offset·w^i)` for every `f` of degree below the size.
+    pub fn lagrange_coefficients<E: ExtensionField<F>>(&self, x: E) -> Vec<E> {
+        let n = self.size();
+        let z = self.evaluate_vanishing(x);
+        if z == E::zero() {
+            // x is a domain point: the basis is an indicator
+            let mut out = vec![E::zero(); n];
+            if let Some(i) = (0..n).find(|&i| E::from(self.element(i)) == x) { out[i] = E::one(); }
+            return out;
+        }
+        // L_i(x) = Z(x) · x_i / (n · offset^n · (x - x_i)), since Z'(x_i) = n·x_i^(n-1) = n·offset^n / x_i
+        let xs = self.elements();
+        let denoms: Vec<E> = xs.iter().map(|&xi| x - E::from(xi)).collect();
+        let scale = z * (self.size_inv * self.offset.pow(n as u128).inv());
+        batch_inverse(&denoms).into_iter().zip(xs).map(|(d, xi)| scale * d * xi).collect()
+    }
+
+    /// `f(x)` for the polynomial of degree below the size with `evals` on the domain, by the
+    /// barycentric formula: no interpolation, one inversion and `O(size)` multiplications.
+    pub fn evaluate_at<E, S>(&self, evals: &[S], x: E) -> E
+    where
+        E: ExtensionField<F> + Mul<S, Output = E>,
+        S: Copy,
+    {
+        assert_eq!(evals.len(), self.size(), "length must match the domain");
+        dot_product(&self.lagrange_coefficients(x), evals)
+    }
+
+    /// [`evaluate_at`](Self::evaluate_at) for many columns at the same point, sharing the
+    /// Lagrange coefficients between them.
+    pub fn batch_evaluate_at<E, S, C>(&self, columns: &[C], x: E) -> Vec<E>
+    where
+        E: ExtensionField<F> + Mul<S, Output = E> + Send + Sync,
+        S: Copy,
+        C: AsRef<[S]> + Sync,
+    {
+        let l = self.lagrange_coefficients(x);
+        columns
+            .par_iter()
+            .map(|c| {
+                assert_eq!(c.as_ref().len(), self.size(), "length must match the domain");
+                dot_product(&l, c.as_ref())
+            })
+            .collect()
+    }
+}
+
+/// `a[i] *= s^i`, i.e. substitute `s·X` for `X` in the coefficients `a`.
+pub(crate) fn scale_by_powers<F: TwoAdicField, E: ExtensionField<F>>(a: &mut [E], s: F) {
+    if s == F::one() { return; }
+    let mut p = F::one();
+    for c in a.iter_mut() {
+        *c = *c * p;
+        p *= s;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Poly;
+    use numiproof_field::{root_of_unity, BabyBear, Field, Fp, Fp3};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn elements_and_vanishing() {
+        let shift = Fp::coset_shift();
+        for size in [1usize, 2, 16, 64] {
+            let d = Radix2Domain::new_coset(size, shift);
+            let w = root_of_unity(size.trailing_zeros());
+            let xs = d.elements();
+            assert_eq!(xs.len(), size);
+            for (i, x) in xs.iter().enumerate() {
+                assert_eq!(*x, shift * w.pow(i as u128));
+                assert_eq!(d.element(i), *x);
+                assert_eq!(d.element(i + size), *x);
+                assert_eq!(d.evaluate_vanishing(*x), Fp::zero());
+            }
+            assert_ne!(d.evaluate_vanishing(Fp::one()), Fp::zero());
+        }
+    }
+
+    #[test]
+    fn domain_fft_matches_free_fft() {
+        let mut rng = StdRng::seed_from_u64(17);
+        for log_n in 0..=9 {
+            let d = Radix2Domain::<Fp>::new(1 << log_n);
+            let coeffs: Vec<Fp> = (0..d.size()).map(|_| Fp::new(rng.gen())).collect();
+            let mut a = coeffs.clone();
+            d.fft(&mut a);
+            let mut b = coeffs.clone();
+            crate::fft_in_place(&mut b, d.generator());
+            assert_eq!(a, b);
+            let poly = Poly::new(coeffs.clone());
+            assert!(d.elements().iter().zip(&a).all(|(x, v)| poly.eval(*x) == *v));
+            d.ifft(&mut a);
+            assert_eq!(a, coeffs);
+        }
+    }
+
+    #[test]
+    fn coset_round_trips() {
+        let mut rng = StdRng::seed_from_u64(19);
+        for log_n in 0..=8 {
+            let d = Radix2Domain::new_coset(1 << log_n, Fp::coset_shift());
+            let coeffs: Vec<Fp> = (0..d.size() / 2 + 1).map(|_| Fp::new(rng.gen())).collect();
+            let evals = d.evaluate(&coeffs);
+            let poly = Poly::new(coeffs.clone());
+            assert!(d.elements().iter().zip(&evals).all(|(x, v)| poly.eval(*x) == *v));
+            let back = d.interpolate(&evals);
+            assert_eq!(&back[..coeffs.len()], &coeffs[..]);
+            assert!(back[coeffs.len()..].iter().all(|c| *c == Fp::zero()));
+        }
+        // Extension values over a BabyBear coset
+        let d = Radix2Domain::new_coset(64, BabyBear::new(3));
+        let evals: Vec<numiproof_field::BabyBear4> =
+            (0..64).map(|_| numiproof_field::BabyBear4(core::array::from_fn(|_| BabyBear::new(rng.gen())))).collect();
+        let mut a = evals.clone();
+        d.coset_ifft(&mut a);
+        d.coset_fft(&mut a);
+        assert_eq!(a, evals);
+    }
+
+    #[test]
+    fn lagrange_coefficients_interpolate() {
+        let mut rng = StdRng::seed_from_u64(18);
+        let d = Radix2Domain::new_coset(32, BabyBear::coset_shift());
+        let poly = Poly::new((0..32).map(|_| BabyBear::new(rng.gen())).collect());
+        let evals: Vec<BabyBear> = d.elements().iter().map(|&x| poly.eval(x)).collect();
+        for x in [BabyBear::new(5), d.element(7), BabyBear::zero()] {
+            let l = d.lagrange_coefficients(x);
+            let v = l.iter().zip(&evals).fold(BabyBear::zero(), |acc, (l, e)| acc + *l * *e);
+            assert_eq!(v, poly.eval(x));
+        }
+        // Out-of-domain points in an extension
+        let d = Radix2Domain::<Fp>::new(16);
+        let poly = Poly::new((0..16).map(|_| Fp::new(rng.gen())).collect());
+        let evals: Vec<Fp> = d.elements().iter().map(|&x| poly.eval(x)).collect();
+        let z = Fp3([Fp::new(3), Fp::new(1), Fp::new(4)]);
+        let l = d.lagrange_coefficients(z);
+        let v = l.iter().zip(&evals).fold(Fp3::zero(), |acc, (l, e)| acc + *l * *e);
+        let expected = poly.coeffs.iter().rev().fold(Fp3::zero(), |acc, &c| acc * z + Fp3::from(c));
+        assert_eq!(v, expected);
+    }
+
+    #[test]
+    fn barycentric_evaluation_matches_horner() {
+        let mut rng = StdRng::seed_from_u64(20);
+        for (size, offset) in [(1usize, Fp::one()), (16, Fp::one()), (64, Fp::coset_shift())] {
+            let d = Radix2Domain::new_coset(size, offset);
+            let polys: Vec<Poly<Fp>> = (0..3).map(|_| Poly::new((0..size).map(|_| Fp::new(rng.gen())).collect())).collect();
+            let columns: Vec<Vec<Fp>> = polys.iter().map(|p| d.evaluate(&p.coeffs)).collect();
+            // Outside the domain, on it, and in the extension
+            for x in [Fp::new(rng.gen()), d.element(size / 2 + 1)] {
+                for (p, col) in polys.iter().zip(&columns) {
+                    assert_eq!(d.evaluate_at(col, x), p.eval(x));
+                }
+            }
+            let z = Fp3(core::array::from_fn(|_| Fp::new(rng.gen())));
+            let expected: Vec<Fp3> =
+                polys.iter().map(|p| p.coeffs.iter().rev().fold(Fp3::zero(), |acc, &c| acc * z + Fp3::from(c))).collect();
+            assert_eq!(d.batch_evaluate_at(&columns, z), expected);
+            // Extension-valued evaluations
+            let ext_cols: Vec<Vec<Fp3>> = columns.iter().map(|c| c.iter().map(|&v| Fp3::from(v) * z).collect()).collect();
+            assert_eq!(d.evaluate_at(&ext_cols[0], z), expected[0] * z);
+        }
+        // BabyBear coset
+        let d = Radix2Domain::new_coset(32, BabyBear::coset_shift());
+        let poly = Poly::new((0..20).map(|_| BabyBear::new(rng.gen())).collect());
+        let evals = d.evaluate(&poly.coeffs);
+        let x = BabyBear::new(rng.gen());
+        assert_eq!(d.evaluate_at(&evals, x), poly.eval(x));
+    }
+}
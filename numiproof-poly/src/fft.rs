@@ -0,0 +1,119 @@
+// File: numiproof-poly/src/fft.rs
+//! Radix-2/4 decimation-in-time butterflies. Pairs of radix-2 stages are fused into one radix-4
+//! pass, halving the sweeps over memory, and large transforms are split across the rayon pool:
+//! over independent blocks in the early passes and over the butterflies of each block in the
+//! late ones.
+use numiproof_field::{bit_reverse, ExtensionField, TwoAdicField};
+use rayon::prelude::*;
+
+/// Transforms (and blocks within a pass) below this size run on the calling thread.
+const PAR_MIN: usize = 1 << 14;
+/// Butterflies per rayon task inside a large block.
+const PAR_CHUNK: usize = 1 << 10;
+
+/// Twiddles for every stage of a size-`n` transform with principal root `w`, laid out stage by
+/// stage: the block-size-`m` stage reads `w_m^k = w^(k·n/m)` for `k < m/2` contiguously at
+/// offset `m/2 - 1`, so no pass strides through a table larger than it needs. `n - 1` entries,
+/// the last `n/2` of which are `w^k` itself.
+pub(crate) fn stage_twiddles<F: TwoAdicField>(root: F, n: usize) -> Vec<F> {
+    let mut out = vec![F::zero(); n.saturating_sub(1)];
+    if n < 2 { return out; }
+    let top = &mut out[n / 2 - 1..];
+    let mut p = F::one();
+    for t in top.iter_mut() {
+        *t = p;
+        p *= root;
+    }
+    // w_m^k = w_2m^(2k)
+    let mut m = n / 2;
+    while m >= 2 {
+        let (lower, upper) = out.split_at_mut(m - 1);
+        let (dst, src) = (&mut lower[m / 2 - 1..], &upper[..m]);
+        for (k, d) in dst.iter_mut().enumerate() { *d = src[2 * k]; }
+        m /= 2;
+    }
+    out
+}
+
+/// In-place FFT given the [`stage_twiddles`] of its size.
+pub(crate) fn fft_with_twiddles<F: TwoAdicField, E: ExtensionField<F>>(a: &mut [E], twiddles: &[F]) {
+    let n = a.len();
+    assert!(n.is_power_of_two());
+    debug_assert_eq!(twiddles.len(), n - 1);
+    bit_reverse_permute(a);
+    let log_n = n.trailing_zeros();
+    // half-size of the butterflies in the next stage
+    let mut h = 1usize;
+    if log_n % 2 == 1 {
+        // Block size 2: the only twiddle is 1
+        for_each_block(a, 2, |block| {
+            let (u, v) = (block[0], block[1]);
+            block[0] = u + v;
+            block[1] = u - v;
+        });
+        h = 2;
+    }
+    while h < n {
+        // w_4h^k for k < 2h
+        let tw = &twiddles[2 * h - 1..4 * h - 1];
+        for_each_block(a, 4 * h, |block| {
+            let (q01, q23) = block.split_at_mut(2 * h);
+            let (q0, q1) = q01.split_at_mut(h);
+            let (q2, q3) = q23.split_at_mut(h);
+            if h >= PAR_MIN {
+                q0.par_chunks_mut(PAR_CHUNK)
+                    .zip(q1.par_chunks_mut(PAR_CHUNK))
+                    .zip(q2.par_chunks_mut(PAR_CHUNK))
+                    .zip(q3.par_chunks_mut(PAR_CHUNK))
+                    .enumerate()
+                    .for_each(|(c, (((q0, q1), q2), q3))| radix4(q0, q1, q2, q3, c * PAR_CHUNK, h, tw));
+            } else {
+                radix4(q0, q1, q2, q3, 0, h, tw);
+            }
+        });
+        h *= 4;
+    }
+}
+
+/// Apply `f` to every consecutive block of `size` elements; for large inputs, runs of blocks
+/// totalling at least `PAR_MIN` elements go to separate rayon tasks.
+fn for_each_block<E: Send, G: Fn(&mut [E]) + Send + Sync>(a: &mut [E], size: usize, f: G) {
+    if a.len() >= PAR_MIN {
+        a.par_chunks_mut(size.max(PAR_MIN)).for_each(|run| run.chunks_mut(size).for_each(&f));
+    } else {
+        a.chunks_mut(size).for_each(f);
+    }
+}
+
+/// Stages `2h` and `4h` fused, on a block of `4h` split into quarters, given `tw[k] = w_4h^k`
+/// for `k < 2h`. Stage `2h` combines `(x0, x1)` and `(x2, x3)` with `w_2h^j = w_4h^(2j)`, then
+/// stage `4h` combines `(y0, y2)` with `w_4h^j` and `(y1, y3)` with `w_4h^(j+h)`.
+fn radix4<F: TwoAdicField, E: ExtensionField<F>>(
+    q0: &mut [E],
+    q1: &mut [E],
+    q2: &mut [E],
+    q3: &mut [E],
+    j0: usize,
+    h: usize,
+    tw: &[F],
+) {
+    for j in 0..q0.len() {
+        let jj = j0 + j;
+        let (w1, w2, w3) = (tw[2 * jj], tw[jj], tw[jj + h]);
+        let (x0, x1, x2, x3) = (q0[j], q1[j] * w1, q2[j], q3[j] * w1);
+        let (y0, y1, y2, y3) = (x0 + x1, x0 - x1, (x2 + x3) * w2, (x2 - x3) * w3);
+        q0[j] = y0 + y2;
+        q2[j] = y0 - y2;
+        q1[j] = y1 + y3;
+        q3[j] = y1 - y3;
+    }
+}
+
+fn bit_reverse_permute<E>(a: &mut [E]) {
+    let n = a.len();
+    let bits = n.trailing_zeros();
+    for i in 0..n {
+        let j = bit_reverse(i, bits);
+        if j > i { a.swap(i, j); }
+    }
+}
@@ -1,148 +1,146 @@
-use numiproof_field::{bit_reverse, root_of_unity, Fp};
-use serde::{Deserialize, Serialize};
+use numiproof_field::{scale_slice, ExtensionField, TwoAdicField};
 
-#[derive(Clone, Serialize, Deserialize, Debug)]
-pub struct Poly {
-    /// Coefficients in little-endian order: a_0 + a_1 X + ...
-    pub coeffs: Vec<Fp>,
-}
-
-impl Poly {
-    pub fn new(coeffs: Vec<Fp>) -> Self { Self { coeffs } }
-    pub fn degree(&self) -> usize { self.coeffs.len().saturating_sub(1) }
-    pub fn eval(&self, x: Fp) -> Fp {
-        let mut acc = Fp::zero();
-        for &c in self.coeffs.iter().rev() { acc = acc * x + c; }
-        acc
-    }
-}
+mod domain;
+mod fft;
+mod poly;
+pub use domain::Radix2Domain;
+pub use poly::Poly;
+use domain::scale_by_powers;
+use fft::{fft_with_twiddles, stage_twiddles};
+use rayon::prelude::*;
 
-/// In-place radix-2 decimation-in-time FFT over size n (power of two); uses \(w\) as a principal \(n\)-th root.
-pub fn fft_in_place(a: &mut [Fp], root: Fp) {
-    let n = a.len();
-    assert!(n.is_power_of_two());
-    // bit-reverse permutation
-    let bits = n.trailing_zeros();
-    for i in 0..n {
-        let j = bit_reverse(i, bits);
-        if j > i { a.swap(i, j); }
-    }
-    let mut m = 2usize;
-    while m <= n {
-        // w_m is primitive m-th root: w^(n/m)
-        let w_m = root.pow((n / m) as u128);
-        for k in (0..n).step_by(m) {
-            let mut w_j = Fp::one();
-            for j in 0..(m/2) {
-                let t = w_j * a[k + j + m/2];
-                let u = a[k + j];
-                a[k + j] = u + t;
-                a[k + j + m/2] = u - t;
-                w_j *= w_m;
-            }
-        }
-        m <<= 1;
-    }
+/// In-place decimation-in-time FFT over size n (power of two), radix-4 with one radix-2 stage for odd
+/// log sizes and parallel above a few thousand points; uses \(w\) as a principal \(n\)-th root.
+/// Values may lie in an extension `E` of the field `F`; the twiddles always lie in `F`. Repeated
+/// transforms of one size should go through a [`Radix2Domain`], which keeps its twiddles.
+pub fn fft_in_place<F: TwoAdicField, E: ExtensionField<F>>(a: &mut [E], root: F) {
+    fft_with_twiddles(a, &stage_twiddles(root, a.len()));
 }
 
-// Note: helper `bits_for_len` was removed to satisfy dead_code lints
-
-pub fn ifft_in_place(a: &mut [Fp], root: Fp) {
+pub fn ifft_in_place<F: TwoAdicField, E: ExtensionField<F>>(a: &mut [E], root: F) {
     // IFFT implemented as FFT with inverse root, then scale by n^{-1}
     let n = a.len();
     let inv_root = root.inv();
     fft_in_place(a, inv_root);
-    let inv_n = Fp::new(n as u64).inv();
-    for x in a.iter_mut() { *x *= inv_n; }
+    let inv_n = F::from_u64(n as u64).inv();
+    scale_slice(a, inv_n);
+}
+
+/// Evaluate coefficients on the coset `shift·<w>` of size `a.len()`, in place.
+pub fn coset_fft<F: TwoAdicField, E: ExtensionField<F>>(a: &mut [E], shift: F) {
+    scale_by_powers(a, shift);
+    fft_in_place(a, F::root_of_unity(a.len().trailing_zeros()));
+}
+
+/// Interpolate evaluations on the coset `shift·<w>` of size `a.len()` into coefficients, in place.
+pub fn coset_ifft<F: TwoAdicField, E: ExtensionField<F>>(a: &mut [E], shift: F) {
+    ifft_in_place(a, F::root_of_unity(a.len().trailing_zeros()));
+    scale_by_powers(a, shift.inv());
 }
 
 /// Evaluate polynomial coefficients on a size-extended radix-2 domain via zero-padding and FFT.
-pub fn lde(coeffs: &[Fp], blowup_log2: u32) -> Vec<Fp> {
+pub fn lde<F: TwoAdicField>(coeffs: &[F], blowup_log2: u32) -> Vec<F> {
     let n = coeffs.len().next_power_of_two();
     let size = n << blowup_log2;
-    let k = size.trailing_zeros();
     // Build evaluation vector by zero-padding to n and NTT to size with twiddle factors.
-    let mut a = vec![Fp::zero(); size];
+    let mut a = vec![F::zero(); size];
     if !coeffs.is_empty() {
         let count = coeffs.len();
         a[..count].copy_from_slice(&coeffs[..count]);
     }
-    // Compute root for size
-    let w = root_of_unity(k);
-    fft_in_place(&mut a, w);
+    Radix2Domain::new(size).fft(&mut a);
     a
 }
 
-/// LDE from base-domain evaluations (size n) to extended evaluations (size n<<blowup).
+/// Low-degree extend evaluations from a base domain (size n) to an extended domain (size n * 2^blowup_log2).
 /// Assumes base domain is the radix-2 subgroup of size n. Pads evaluations by duplicating
 /// the last value up to the next power-of-two, then converts to coeffs and re-evaluates on the
 /// extended domain.
-pub fn lde_from_evals(base_evals: &[Fp], blowup_log2: u32) -> Vec<Fp> {
+pub fn lde_from_evals<F: TwoAdicField>(base_evals: &[F], blowup_log2: u32) -> Vec<F> {
     let n_base = base_evals.len().next_power_of_two();
     let ext_size = n_base << blowup_log2;
     // Copy and pad base evaluations
-    let mut evals = vec![Fp::zero(); n_base];
+    let mut evals = vec![F::zero(); n_base];
     if !base_evals.is_empty() {
         let count = base_evals.len();
         evals[..count].copy_from_slice(&base_evals[..count]);
         if count < n_base {
-            let last = *base_evals.last().unwrap();
-            for x in evals[count..n_base].iter_mut() { *x = last; }
+            let last = base_evals[count - 1];
+            for v in evals.iter_mut().skip(count) { *v = last; }
         }
     }
     // Inverse FFT on base domain to get coefficients
-    let w_base = root_of_unity(n_base.trailing_zeros());
-    ifft_in_place(&mut evals, w_base);
+    Radix2Domain::new(n_base).ifft(&mut evals);
     // Zero-pad coefficients to extended size
-    let mut coeffs_ext = vec![Fp::zero(); ext_size];
+    let mut coeffs_ext = vec![F::zero(); ext_size];
     coeffs_ext[..n_base].copy_from_slice(&evals[..n_base]);
     // FFT to extended domain
-    let w_ext = root_of_unity(ext_size.trailing_zeros());
-    fft_in_place(&mut coeffs_ext, w_ext);
+    Radix2Domain::new(ext_size).fft(&mut coeffs_ext);
     coeffs_ext
 }
 
-pub fn vanishing_poly_evals(size: usize) -> Vec<Fp> {
+/// Low-degree extend evaluations on the trace subgroup (padded by repeating the last value, as
+/// in [`lde_from_evals`]) onto the coset `shift·<w'>` of size `n * 2^blowup_log2`. With `shift`
+/// outside the subgroups, no LDE point lies in the trace domain, so quotients by its vanishing
+/// polynomial are defined everywhere on the result.
+pub fn coset_lde<F: TwoAdicField>(evals: &[F], blowup_log2: u32, shift: F) -> Vec<F> {
+    assert!(!evals.is_empty(), "nothing to extend");
+    let n_base = evals.len().next_power_of_two();
+    let mut coeffs = evals.to_vec();
+    coeffs.resize(n_base, evals[evals.len() - 1]);
+    Radix2Domain::new(n_base).ifft(&mut coeffs);
+    Radix2Domain::new_coset(n_base << blowup_log2, shift).evaluate(&coeffs)
+}
+
+/// [`lde_from_evals`] for every column at once: the twiddle tables are built once and the
+/// columns are transformed in parallel.
+pub fn batch_lde<F: TwoAdicField>(columns: &[Vec<F>], blowup_log2: u32) -> Vec<Vec<F>> {
+    batch_coset_lde(columns, blowup_log2, F::one())
+}
+
+/// [`coset_lde`] for every column at once, sharing twiddles and running the columns in parallel.
+/// All columns must have the same length.
+pub fn batch_coset_lde<F: TwoAdicField>(columns: &[Vec<F>], blowup_log2: u32, shift: F) -> Vec<Vec<F>> {
+    let Some(first) = columns.first() else { return Vec::new() };
+    assert!(columns.iter().all(|c| c.len() == first.len() && !c.is_empty()), "columns must share a non-empty length");
+    let n_base = first.len().next_power_of_two();
+    let base = Radix2Domain::new(n_base);
+    let ext = Radix2Domain::new_coset(n_base << blowup_log2, shift);
+    columns
+        .par_iter()
+        .map(|c| {
+            let mut coeffs = c.clone();
+            coeffs.resize(n_base, c[c.len() - 1]);
+            base.ifft(&mut coeffs);
+            ext.evaluate(&coeffs)
+        })
+        .collect()
+}
+
+pub fn vanishing_poly_evals<F: TwoAdicField>(size: usize) -> Vec<F> {
     // On evaluation domain of size N with root w and generator 1, vanishes at all points => X^N - 1
     // Return evaluations of z(x) = x^N - 1 on the domain [1, w, w^2, ...]
-    assert!(size.is_power_of_two());
-    let k = size.trailing_zeros();
-    let w = root_of_unity(k);
-    let mut evals = Vec::with_capacity(size);
-    let mut x = Fp::one();
-    for _ in 0..size {
-        evals.push(x.pow(size as u128) - Fp::one());
-        x *= w;
-    }
-    evals
+    let domain = Radix2Domain::new(size);
+    domain.elements().into_iter().map(|x| domain.evaluate_vanishing(x)).collect()
 }
 
 /// Evaluate z_base(x) = x^{base_size} - 1 over a domain of length `domain_size`.
-pub fn vanishing_on_extended(domain_size: usize, base_size: usize) -> Vec<Fp> {
-    assert!(domain_size.is_power_of_two());
-    let k = domain_size.trailing_zeros();
-    let w = root_of_unity(k);
-    let mut evals = Vec::with_capacity(domain_size);
-    let mut x = Fp::one();
-    for _ in 0..domain_size {
-        evals.push(x.pow(base_size as u128) - Fp::one());
-        x *= w;
-    }
-    evals
+pub fn vanishing_on_extended<F: TwoAdicField>(domain_size: usize, base_size: usize) -> Vec<F> {
+    Radix2Domain::<F>::new(domain_size)
+        .elements()
+        .into_iter()
+        .map(|x| x.pow(base_size as u128) - F::one())
+        .collect()
 }
 
 /// Evaluate a small-degree polynomial with `coeffs` at each point of a radix-2
 /// domain of size `domain_size` (points: 1, w, w^2, ...).
-pub fn eval_poly_on_domain(coeffs: &[Fp], domain_size: usize) -> Vec<Fp> {
-    assert!(domain_size.is_power_of_two());
-    let k = domain_size.trailing_zeros();
-    let w = root_of_unity(k);
-    let mut xs = Vec::with_capacity(domain_size);
-    let mut x = Fp::one();
-    for _ in 0..domain_size { xs.push(x); x *= w; }
-    xs.into_iter()
+pub fn eval_poly_on_domain<F: TwoAdicField>(coeffs: &[F], domain_size: usize) -> Vec<F> {
+    Radix2Domain::<F>::new(domain_size)
+        .elements()
+        .into_iter()
         .map(|x| {
-            let mut acc = Fp::zero();
+            let mut acc = F::zero();
             for &c in coeffs.iter().rev() { acc = acc * x + c; }
             acc
         })
@@ -152,6 +150,7 @@ pub fn eval_poly_on_domain(coeffs: &[Fp], domain_size: usize) -> Vec<Fp> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use numiproof_field::{root_of_unity, BabyBear, Field, Fp};
     use rand::{Rng, SeedableRng};
     use rand::rngs::StdRng;
     #[test]
@@ -186,6 +185,38 @@ mod tests {
         }
     }
 
+    #[test]
+    fn babybear_lde_passes_through_base_points() {
+        let mut rng = StdRng::seed_from_u64(27);
+        let base: Vec<BabyBear> = (0..64).map(|_| BabyBear::new(rng.gen())).collect();
+        let ext = lde_from_evals(&base, 3);
+        assert_eq!(ext.len(), 512);
+        assert!((0..64).all(|i| ext[i << 3] == base[i]));
+        let mut back = ext.clone();
+        let w = BabyBear::root_of_unity(9);
+        ifft_in_place(&mut back, w);
+        // Degree below 64, so the top coefficients vanish
+        assert!(back[64..].iter().all(|c| *c == BabyBear::zero()));
+    }
+
+    #[test]
+    fn extension_fft_acts_coordinate_wise() {
+        use numiproof_field::Fp3;
+        let mut rng = StdRng::seed_from_u64(3);
+        let n = 64;
+        let w = root_of_unity(6);
+        let mut a: Vec<Fp3> = (0..n).map(|_| Fp3([Fp::new(rng.gen()), Fp::new(rng.gen()), Fp::new(rng.gen())])).collect();
+        let original = a.clone();
+        fft_in_place(&mut a, w);
+        for j in 0..3 {
+            let mut coord: Vec<Fp> = original.iter().map(|x| x.0[j]).collect();
+            fft_in_place(&mut coord, w);
+            assert!(a.iter().zip(&coord).all(|(x, c)| x.0[j] == *c));
+        }
+        ifft_in_place(&mut a, w);
+        assert_eq!(a, original);
+    }
+
     #[test]
     fn lde_from_evals_consistency_at_base_points() {
         // For a base-domain vector, the extended domain at coset starts (step size) should match base
@@ -209,11 +240,87 @@ mod tests {
         }
     }
 
+    #[test]
+    fn coset_fft_round_trip_on_shifted_domains() {
+        let mut rng = StdRng::seed_from_u64(18);
+        for log_n in 1..=9 {
+            let n = 1usize << log_n;
+            for shift in [Fp::one(), Fp::coset_shift(), Fp::new(rng.gen())] {
+                let coeffs: Vec<Fp> = (0..n).map(|_| Fp::new(rng.gen())).collect();
+                let mut a = coeffs.clone();
+                coset_fft(&mut a, shift);
+                let w = root_of_unity(log_n);
+                let poly = Poly::new(coeffs.clone());
+                for k in [0, 1, n - 1] {
+                    assert_eq!(a[k], poly.eval(shift * w.pow(k as u128)));
+                }
+                coset_ifft(&mut a, shift);
+                assert_eq!(a, coeffs);
+            }
+        }
+    }
+
+    #[test]
+    fn coset_lde_extends_the_trace_interpolant() {
+        let mut rng = StdRng::seed_from_u64(180);
+        for (n, r) in [(8usize, 1u32), (13, 2), (64, 3)] {
+            let evals: Vec<Fp> = (0..n).map(|_| Fp::new(rng.gen())).collect();
+            let shift = Fp::coset_shift();
+            let ext = coset_lde(&evals, r, shift);
+            let n_pow2 = n.next_power_of_two();
+            assert_eq!(ext.len(), n_pow2 << r);
+            // Interpolating back on the coset gives the subgroup interpolant, which is low degree
+            // and passes through the (padded) trace
+            let mut coeffs = ext.clone();
+            coset_ifft(&mut coeffs, shift);
+            assert!(coeffs[n_pow2..].iter().all(|c| *c == Fp::zero()));
+            let poly = Poly::new(coeffs[..n_pow2].to_vec());
+            let g = root_of_unity(n_pow2.trailing_zeros());
+            for (i, v) in evals.iter().enumerate() {
+                assert_eq!(poly.eval(g.pow(i as u128)), *v);
+            }
+            // No point of the coset lies in the trace domain
+            let d = Radix2Domain::new_coset(ext.len(), shift);
+            assert!(d.elements().iter().all(|x| x.pow(n_pow2 as u128) != Fp::one()));
+        }
+    }
+
+    #[test]
+    fn parallel_fft_matches_serial_dft() {
+        // Sizes on both sides of the parallel threshold, with odd and even log sizes
+        let mut rng = StdRng::seed_from_u64(19);
+        for log_n in [13, 15, 16] {
+            let n = 1usize << log_n;
+            let coeffs: Vec<Fp> = (0..n).map(|_| Fp::new(rng.gen())).collect();
+            let w = root_of_unity(log_n);
+            let mut a = coeffs.clone();
+            fft_in_place(&mut a, w);
+            let poly = Poly::new(coeffs.clone());
+            for k in [0, 1, 2, n / 3, n / 2 + 1, n - 1] {
+                assert_eq!(a[k], poly.eval(w.pow(k as u128)));
+            }
+            ifft_in_place(&mut a, w);
+            assert_eq!(a, coeffs);
+        }
+    }
+
+    #[test]
+    fn batch_lde_matches_per_column() {
+        let mut rng = StdRng::seed_from_u64(191);
+        let cols: Vec<Vec<Fp>> = (0..5).map(|_| (0..100).map(|_| Fp::new(rng.gen())).collect()).collect();
+        let ext = batch_lde(&cols, 2);
+        assert!(ext.iter().zip(&cols).all(|(e, c)| *e == lde_from_evals(c, 2)));
+        let shift = Fp::coset_shift();
+        let ext = batch_coset_lde(&cols, 3, shift);
+        assert!(ext.iter().zip(&cols).all(|(e, c)| *e == coset_lde(c, 3, shift)));
+        assert!(batch_lde::<Fp>(&[], 2).is_empty());
+    }
+
     #[test]
     fn vanishing_polys_correctness() {
         for log_n in 3..=10 {
             let n = 1usize << log_n;
-            let evals = vanishing_poly_evals(n);
+            let evals: Vec<Fp> = vanishing_poly_evals(n);
             let w = root_of_unity(log_n);
             let mut x = Fp::one();
             for ev in evals.iter() {
@@ -229,7 +336,7 @@ mod tests {
             let n = 1usize << log_n;
             let r = 2u32;
             let ext_size = n << r;
-            let evals = vanishing_on_extended(ext_size, n);
+            let evals: Vec<Fp> = vanishing_on_extended(ext_size, n);
             // At every ext index that corresponds to a base point, value should be zero
             for i in (0..ext_size).step_by(1<<r) {
                 assert_eq!(evals[i], Fp::zero());
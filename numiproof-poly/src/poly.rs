@@ -0,0 +1,322 @@
+// File: numiproof-poly/src/poly.rs
+//! Dense univariate polynomials in coefficient form and their arithmetic.
+use core::ops::{Add, Mul, Neg, Sub};
+use numiproof_field::{batch_inverse, Field, Fp, TwoAdicField};
+use serde::{Deserialize, Serialize};
+
+use crate::Radix2Domain;
+
+/// Below this many coefficients in the smaller factor, schoolbook multiplication beats the FFT.
+const FFT_MUL_MIN: usize = 32;
+
+/// Equality ignores trailing zero coefficients.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Poly<F = Fp> {
+    /// Coefficients in little-endian order: a_0 + a_1 X + ...
+    pub coeffs: Vec<F>,
+}
+
+impl<F: Field> Poly<F> {
+    pub fn new(coeffs: Vec<F>) -> Self { Self { coeffs } }
+    pub fn zero() -> Self { Self { coeffs: Vec::new() } }
+    pub fn constant(c: F) -> Self { Self { coeffs: vec![c] } }
+
+    /// Degree, ignoring leading zero coefficients; 0 for constants, including the zero polynomial.
+    pub fn degree(&self) -> usize { self.trimmed_len().saturating_sub(1) }
+    pub fn is_zero(&self) -> bool { self.trimmed_len() == 0 }
+    /// Coefficient of the highest non-zero term, zero for the zero polynomial.
+    pub fn leading_coeff(&self) -> F { self.coeffs[..self.trimmed_len()].last().copied().unwrap_or(F::zero()) }
+
+    /// Drop leading zero coefficients.
+    pub fn trim(&mut self) { self.coeffs.truncate(self.trimmed_len()); }
+
+    fn trimmed_len(&self) -> usize {
+        self.coeffs.iter().rposition(|c| *c != F::zero()).map_or(0, |i| i + 1)
+    }
+
+    pub fn eval(&self, x: F) -> F {
+        let mut acc = F::zero();
+        for &c in self.coeffs.iter().rev() { acc = acc * x + c; }
+        acc
+    }
+
+    /// `c · f(X)`.
+    pub fn scale(&self, c: F) -> Self {
+        Self::new(self.coeffs.iter().map(|&a| a * c).collect())
+    }
+
+    /// `f(g·X)`: coefficient `i` is multiplied by `g^i`. With `g` the trace generator this is the
+    /// next-row polynomial.
+    pub fn compose_scaled(&self, g: F) -> Self {
+        let mut p = F::one();
+        let coeffs = self
+            .coeffs
+            .iter()
+            .map(|&a| {
+                let c = a * p;
+                p *= g;
+                c
+            })
+            .collect();
+        Self::new(coeffs)
+    }
+
+    /// Schoolbook product, for any field and any sizes.
+    pub fn naive_mul(&self, rhs: &Self) -> Self {
+        let (a, b) = (&self.coeffs[..self.trimmed_len()], &rhs.coeffs[..rhs.trimmed_len()]);
+        if a.is_empty() || b.is_empty() { return Self::zero(); }
+        let mut out = vec![F::zero(); a.len() + b.len() - 1];
+        for (i, &x) in a.iter().enumerate() {
+            for (j, &y) in b.iter().enumerate() {
+                out[i + j] += x * y;
+            }
+        }
+        Self::new(out)
+    }
+
+    /// Quotient and remainder of Euclidean division by `divisor`, which must be non-zero.
+    pub fn div_rem(&self, divisor: &Self) -> (Self, Self) {
+        let d_len = divisor.trimmed_len();
+        assert!(d_len > 0, "division by the zero polynomial");
+        let mut rem = self.coeffs[..self.trimmed_len()].to_vec();
+        if rem.len() < d_len { return (Self::zero(), Self::new(rem)); }
+        let lead_inv = divisor.coeffs[d_len - 1].inv();
+        let mut quot = vec![F::zero(); rem.len() - d_len + 1];
+        for i in (0..quot.len()).rev() {
+            let q = rem[i + d_len - 1] * lead_inv;
+            quot[i] = q;
+            for (r, &d) in rem[i..i + d_len].iter_mut().zip(&divisor.coeffs[..d_len]) {
+                *r -= q * d;
+            }
+        }
+        rem.truncate(d_len - 1);
+        let mut rem = Self::new(rem);
+        rem.trim();
+        (Self::new(quot), rem)
+    }
+
+    /// Quotient by `X - z` and the remainder, which is `f(z)` (synthetic division).
+    pub fn div_by_linear(&self, z: F) -> (Self, F) {
+        let n = self.coeffs.len();
+        if n == 0 { return (Self::zero(), F::zero()); }
+        let mut quot = vec![F::zero(); n - 1];
+        let mut acc = F::zero();
+        for i in (0..n).rev() {
+            acc = acc * z + self.coeffs[i];
+            if i > 0 { quot[i - 1] = acc; }
+        }
+        (Self::new(quot), acc)
+    }
+
+    /// Quotient and remainder of division by `X^n - c`, in linear time; `n` must be positive.
+    pub fn div_by_binomial(&self, n: usize, c: F) -> (Self, Self) {
+        assert!(n > 0, "X^0 - c is a constant");
+        let mut rem = self.coeffs[..self.trimmed_len()].to_vec();
+        if rem.len() <= n { return (Self::zero(), Self::new(rem)); }
+        let mut quot = vec![F::zero(); rem.len() - n];
+        // X^i = X^(i-n)·(X^n - c) + c·X^(i-n)
+        for i in (n..rem.len()).rev() {
+            let top = rem[i];
+            quot[i - n] = top;
+            rem[i - n] += c * top;
+        }
+        rem.truncate(n);
+        let mut rem = Self::new(rem);
+        rem.trim();
+        (Self::new(quot), rem)
+    }
+
+    /// The unique polynomial of degree below `points.len()` through `(x_i, y_i)`; the `x_i` must
+    /// be distinct. Quadratic time, for small or irregular point sets.
+    pub fn interpolate(points: &[(F, F)]) -> Self {
+        // M(X) = prod (X - x_i); L_i = M / (X - x_i) / M'(x_i)
+        let mut m = Self::constant(F::one());
+        for &(x, _) in points {
+            m = m.naive_mul(&Self::new(vec![-x, F::one()]));
+        }
+        let numerators: Vec<Self> = points.iter().map(|&(x, _)| m.div_by_linear(x).0).collect();
+        let denoms: Vec<F> = numerators.iter().zip(points).map(|(n, &(x, _))| n.eval(x)).collect();
+        assert!(denoms.iter().all(|d| *d != F::zero()), "interpolation points must be distinct");
+        let mut out = vec![F::zero(); points.len()];
+        for ((n, &(_, y)), d_inv) in numerators.iter().zip(points).zip(batch_inverse(&denoms)) {
+            let w = y * d_inv;
+            for (o, &c) in out.iter_mut().zip(&n.coeffs) {
+                *o += w * c;
+            }
+        }
+        let mut p = Self::new(out);
+        p.trim();
+        p
+    }
+}
+
+impl<F: TwoAdicField> Poly<F> {
+    /// Product via FFT over a power-of-two domain of the product's size.
+    pub fn fft_mul(&self, rhs: &Self) -> Self {
+        let (la, lb) = (self.trimmed_len(), rhs.trimmed_len());
+        if la == 0 || lb == 0 { return Self::zero(); }
+        let domain = Radix2Domain::new((la + lb - 1).next_power_of_two());
+        let a = domain.evaluate(&self.coeffs[..la]);
+        let b = domain.evaluate(&rhs.coeffs[..lb]);
+        let prod: Vec<F> = a.into_iter().zip(b).map(|(x, y)| x * y).collect();
+        let mut out = domain.interpolate(&prod);
+        out.truncate(la + lb - 1);
+        Self::new(out)
+    }
+}
+
+impl<F: Field> PartialEq for Poly<F> {
+    fn eq(&self, other: &Self) -> bool {
+        self.coeffs[..self.trimmed_len()] == other.coeffs[..other.trimmed_len()]
+    }
+}
+impl<F: Field> Eq for Poly<F> {}
+
+impl<F: Field> Add for &Poly<F> {
+    type Output = Poly<F>;
+    fn add(self, rhs: Self) -> Poly<F> {
+        let (long, short) = if self.coeffs.len() >= rhs.coeffs.len() { (self, rhs) } else { (rhs, self) };
+        let mut out = long.coeffs.clone();
+        for (o, &c) in out.iter_mut().zip(&short.coeffs) { *o += c; }
+        let mut p = Poly::new(out);
+        p.trim();
+        p
+    }
+}
+
+impl<F: Field> Sub for &Poly<F> {
+    type Output = Poly<F>;
+    fn sub(self, rhs: Self) -> Poly<F> { self + &-rhs }
+}
+
+impl<F: Field> Neg for &Poly<F> {
+    type Output = Poly<F>;
+    fn neg(self) -> Poly<F> { Poly::new(self.coeffs.iter().map(|&c| -c).collect()) }
+}
+
+/// FFT-based once both factors have at least `FFT_MUL_MIN` coefficients.
+impl<F: TwoAdicField> Mul for &Poly<F> {
+    type Output = Poly<F>;
+    fn mul(self, rhs: Self) -> Poly<F> {
+        if self.trimmed_len().min(rhs.trimmed_len()) < FFT_MUL_MIN { self.naive_mul(rhs) } else { self.fft_mul(rhs) }
+    }
+}
+
+impl<F: Field> Add for Poly<F> {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self { &self + &rhs }
+}
+impl<F: Field> Sub for Poly<F> {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self { &self - &rhs }
+}
+impl<F: Field> Neg for Poly<F> {
+    type Output = Self;
+    fn neg(self) -> Self { -&self }
+}
+impl<F: TwoAdicField> Mul for Poly<F> {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self { &self * &rhs }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use numiproof_field::{root_of_unity, BabyBear, Fp3, PrimeField};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn random<F: PrimeField>(rng: &mut StdRng, len: usize) -> Poly<F> {
+        Poly::new((0..len).map(|_| F::from_u64(rng.gen())).collect())
+    }
+
+    /// Every identity is checked by evaluating at random points.
+    #[test]
+    fn ring_operations_agree_with_evaluation() {
+        let mut rng = StdRng::seed_from_u64(20);
+        for _ in 0..40 {
+            let (la, lb) = (rng.gen_range(0..80), rng.gen_range(0..80));
+            let (a, b): (Poly<Fp>, Poly<Fp>) = (random(&mut rng, la), random(&mut rng, lb));
+            let (sum, diff, prod) = (&a + &b, &a - &b, &a * &b);
+            assert_eq!(prod, a.naive_mul(&b));
+            assert_eq!(a.fft_mul(&b), a.naive_mul(&b));
+            if !a.is_zero() && !b.is_zero() { assert_eq!(prod.degree(), a.degree() + b.degree()); }
+            for _ in 0..3 {
+                let x = Fp::new(rng.gen());
+                assert_eq!(sum.eval(x), a.eval(x) + b.eval(x));
+                assert_eq!(diff.eval(x), a.eval(x) - b.eval(x));
+                assert_eq!(prod.eval(x), a.eval(x) * b.eval(x));
+            }
+            assert!((&a - &a).is_zero());
+        }
+    }
+
+    #[test]
+    fn degree_ignores_leading_zeros() {
+        let p = Poly::new(vec![Fp::new(1), Fp::new(2), Fp::zero(), Fp::zero()]);
+        assert_eq!(p.degree(), 1);
+        assert_eq!(p.leading_coeff(), Fp::new(2));
+        assert_eq!(p, Poly::new(vec![Fp::new(1), Fp::new(2)]));
+        assert_eq!(Poly::new(vec![Fp::zero(); 3]).degree(), 0);
+        assert!(Poly::new(vec![Fp::zero(); 3]).is_zero());
+        let mut q = p.clone();
+        q.trim();
+        assert_eq!(q.coeffs.len(), 2);
+    }
+
+    #[test]
+    fn division_reconstructs_the_dividend() {
+        let mut rng = StdRng::seed_from_u64(21);
+        for _ in 0..40 {
+            let (la, ld) = (rng.gen_range(0..60), rng.gen_range(1..30));
+            let a: Poly<BabyBear> = random(&mut rng, la);
+            let mut d: Poly<BabyBear> = random(&mut rng, ld);
+            if d.is_zero() { d = Poly::constant(BabyBear::one()); }
+            let (q, r) = a.div_rem(&d);
+            assert_eq!(&(&q * &d) + &r, a);
+            assert!(r.is_zero() || r.degree() < d.degree());
+
+            let z = BabyBear::new(rng.gen());
+            let (q, r) = a.div_by_linear(z);
+            assert_eq!(r, a.eval(z));
+            assert_eq!(&(&q * &Poly::new(vec![-z, BabyBear::one()])) + &Poly::constant(r), a);
+
+            let n = rng.gen_range(1..20);
+            let c = BabyBear::new(rng.gen());
+            let (q, r) = a.div_by_binomial(n, c);
+            let mut binomial = vec![BabyBear::zero(); n + 1];
+            binomial[0] = -c;
+            binomial[n] = BabyBear::one();
+            let binomial = Poly::new(binomial);
+            assert_eq!(&(&q * &binomial) + &r, a);
+            assert_eq!(a.div_rem(&binomial), (q, r));
+        }
+    }
+
+    #[test]
+    fn interpolation_through_arbitrary_points() {
+        let mut rng = StdRng::seed_from_u64(22);
+        for n in [1usize, 2, 7, 25] {
+            let p: Poly<Fp> = random(&mut rng, n);
+            let points: Vec<(Fp, Fp)> = (0..n).map(|_| Fp::new(rng.gen())).map(|x| (x, p.eval(x))).collect();
+            assert_eq!(Poly::interpolate(&points), p);
+        }
+        assert!(Poly::<Fp>::interpolate(&[]).is_zero());
+    }
+
+    #[test]
+    fn composition_with_scaled_variable() {
+        let mut rng = StdRng::seed_from_u64(23);
+        let p: Poly<Fp> = random(&mut rng, 40);
+        let g = root_of_unity(6);
+        let next = p.compose_scaled(g);
+        for _ in 0..5 {
+            let x = Fp::new(rng.gen());
+            assert_eq!(next.eval(x), p.eval(g * x));
+        }
+        // Extension coefficients work for everything not needing an FFT
+        let e = Poly::new(vec![Fp3::from(Fp::new(2)), Fp3([Fp::new(1), Fp::new(5), Fp::new(9)])]);
+        let (q, r) = e.naive_mul(&e).div_rem(&e);
+        assert_eq!((q, r.is_zero()), (e, true));
+    }
+}
@@ -1,6 +1,6 @@
 use serde::{Serialize, Deserialize};
-use numiproof_field::Fp;
-use numiproof_air::Air;
+use numiproof_field::{ExtensionField, Fp};
+use numiproof_air::{Air, Assertion};
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct ShieldedPublic {
@@ -9,6 +9,9 @@ pub struct ShieldedPublic {
     pub prev_root: Vec<u8>,
 }
 
+/// Balance AIR for a shielded transfer. Columns are [balance, value, sign]: the balance starts
+/// and ends at zero and moves by `sign · value` per row, with `sign = 1` on the `n_in` input rows
+/// and `-1` on the output rows.
 #[derive(Clone)]
 pub struct ShieldedAir {
     pub in_values: Vec<u64>,
@@ -23,64 +26,70 @@ impl ShieldedAir {
 }
 
 impl Air for ShieldedAir {
+    type Field = Fp;
     type PublicInput = ShieldedPublic;
-    fn id(&self) -> &'static str { "shielded_v1" }
+    fn id() -> &'static str { "shielded_v2" }
+    fn n_cols() -> usize { 3 }
     fn trace_len(&self) -> usize { self.in_values.len() + self.out_values.len() + 1 }
-    fn n_cols(&self) -> usize { 2 }
     fn public_input(&self) -> Self::PublicInput {
         ShieldedPublic { n_in: self.in_values.len() as u32, n_out: self.out_values.len() as u32, prev_root: self.prev_root.clone() }
     }
     fn gen_trace(&self) -> Vec<Vec<Fp>> {
         let n = self.trace_len();
-        let n_in = self.in_values.len();
-        let n_out = self.out_values.len();
         let mut c0 = vec![Fp::zero(); n];
         let mut c1 = vec![Fp::zero(); n];
-        let sum_in: u128 = self.in_values.iter().map(|&v| v as u128).sum();
-        let sum_out: u128 = self.out_values.iter().map(|&v| v as u128).sum();
-        c0[0] = Fp::from_u128(sum_in.wrapping_sub(sum_out));
-        // fill input rows
-        for i in 0..n_in {
-            c1[i] = Fp::new(self.in_values[i]);
-            c0[i+1] = c0[i] - c1[i];
+        let mut c2 = vec![-Fp::one(); n];
+        // fill input rows, then output rows; the balance returns to zero iff the transfer balances
+        let values = self.in_values.iter().map(|&v| (v, Fp::one()))
+            .chain(self.out_values.iter().map(|&v| (v, -Fp::one())));
+        for (i, (v, sign)) in values.enumerate() {
+            c1[i] = Fp::new(v);
+            c2[i] = sign;
+            c0[i+1] = c0[i] + sign * c1[i];
         }
-        // fill output rows
-        for j in 0..n_out {
-            let i = n_in + j;
-            c1[i] = Fp::new(self.out_values[j]);
-            c0[i+1] = c0[i] + c1[i];
-        }
-        // last row already set; boundary should be zero
-        vec![c0, c1]
+        vec![c0, c1, c2]
     }
-    fn check_row(i: usize, row: &[Fp], next: Option<&[Fp]>, pub_inp: &Self::PublicInput) -> bool {
-        if let Some(nxt) = next {
-            let n_in = pub_inp.n_in as usize;
-            if i < n_in {
-                // input row: next_acc = acc - val
-                if nxt[0] != row[0] - row[1] { return false; }
-            } else {
-                // output row: next_acc = acc + val
-                if nxt[0] != row[0] + row[1] { return false; }
-            }
-        } else {
-            // last row boundary: accumulator is zero
-            if row[0] != Fp::zero() { return false; }
-        }
-        true
+    fn transition_degrees() -> Vec<usize> { vec![2, 2] }
+    fn eval_transition<E: ExtensionField>(row: &[E], next: &[E], _pub_inp: &Self::PublicInput) -> Vec<E> {
+        vec![
+            // next_acc = acc + sign * val
+            next[0] - (row[0] + row[2] * row[1]),
+            // the sign either stays or switches to -1 for good
+            (next[2] - row[2]) * (next[2] + E::one()),
+        ]
     }
-    fn eval_constraints(&self, i: usize, row: &[Fp], next: Option<&[Fp]>, pub_inp: &Self::PublicInput) -> Vec<Fp> {
-        if let Some(nxt) = next {
-            let n_in = pub_inp.n_in as usize;
-            if i < n_in {
-                vec![nxt[0] - (row[0] - row[1]), Fp::zero()]
-            } else {
-                vec![nxt[0] - (row[0] + row[1]), Fp::zero()]
-            }
-        } else {
-            vec![row[0] - Fp::zero(), Fp::zero()]
-        }
+    fn assertions(pub_inp: &Self::PublicInput) -> Vec<Assertion> {
+        let n_in = pub_inp.n_in as usize;
+        let last = n_in + pub_inp.n_out as usize;
+        let mut out = vec![Assertion::new(0, 0, Fp::zero()), Assertion::new(last, 0, Fp::zero())];
+        // Pin the switch from inputs to outputs; with the sign transition this fixes every row
+        if n_in > 0 { out.push(Assertion::new(n_in - 1, 2, Fp::one())); }
+        if n_in < last { out.push(Assertion::new(n_in, 2, -Fp::one())); }
+        out
     }
 }
 
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use numiproof_proof::{FriConfig, Prover, Verifier, VerifierConfig};
+
+    #[test]
+    fn shielded_balanced_prove_verify() {
+        let air = ShieldedAir::new(vec![70, 30], vec![60, 25, 15], vec![0u8; 48]);
+        let prover = Prover { cfg: FriConfig { blowup_log2: 2, num_rounds: 1, queries: 16, ..Default::default() } };
+        let proof = prover.prove(&air);
+        assert!(Verifier::verify_with::<ShieldedAir>(&proof, &VerifierConfig { min_queries: 1, min_blowup_log2: 1, ..Default::default() }).is_ok());
+    }
+
+    #[test]
+    fn shielded_unbalanced_trace_rejected() {
+        let air = ShieldedAir::new(vec![70, 30], vec![60, 25], vec![0u8; 48]);
+        let pub_inp = air.public_input();
+        let cols = air.gen_trace();
+        let last: Vec<Fp> = cols.iter().map(|c| c[4]).collect();
+        assert!(!ShieldedAir::check_row(4, &last, None, &pub_inp));
+    }
+}
@@ -55,8 +55,7 @@ pub fn kem_encapsulate(pk_bytes: &[u8]) -> (Vec<u8>, Vec<u8>) {
 pub fn kem_decapsulate(ct_bytes: &[u8], sk_bytes: &[u8]) -> Vec<u8> {
     let ct = mlkem::Ciphertext { bytes: ct_bytes.to_vec() };
     let sk = mlkem::SecretKey { bytes: sk_bytes.to_vec() };
-    let ss = mlkem::decapsulate(&ct, &sk);
-    ss
+    mlkem::decapsulate(&ct, &sk)
 }
 
 /// Encrypt payload using KEM + XOR stream (simplified; not a full AEAD). For demos only.
@@ -0,0 +1,195 @@
+// File: numiproof-proof/src/composition.rs
+//! Constraint composition: transition and boundary constraints divided by their vanishing
+//! polynomials and combined with transcript challenges into one quotient.
+use core::ops::Mul;
+use numiproof_air::{Air, Assertion};
+use numiproof_field::{batch_inverse, dot_product, ExtensionField};
+use numiproof_fri::DeepSample;
+use numiproof_poly::Poly;
+
+use crate::{Challenge, ProofField};
+
+/// Vanishing data for a trace of `n_rows` rows padded to the subgroup `<g>` of size `n_pow2`,
+/// interpolated (and randomized) by polynomials of degree below `trace_degree`.
+pub(crate) struct ConstraintDomain<F> {
+    n_pow2: usize,
+    trace_degree: usize,
+    g: F,
+    /// Transitions are not enforced from row `n_rows - 1` up to the end of the padded domain
+    first_exempt: usize,
+    /// `g^row` for each assertion
+    assertion_points: Vec<F>,
+}
+
+impl<F: ProofField> ConstraintDomain<F> {
+    pub(crate) fn new(n_rows: usize, trace_degree: usize, assertions: &[Assertion<F>]) -> Self {
+        let n_pow2 = n_rows.next_power_of_two();
+        let g = F::root_of_unity(n_pow2.trailing_zeros());
+        let assertion_points = assertions.iter().map(|a| g.pow(a.row as u128)).collect();
+        Self { n_pow2, trace_degree, g, first_exempt: n_rows - 1, assertion_points }
+    }
+
+    /// Power-of-two bound on the composition degree when transitions have degree `max_degree`.
+    pub(crate) fn degree_bound(&self, max_degree: usize) -> usize {
+        max_degree.max(1).next_power_of_two() * self.trace_degree
+    }
+
+    /// `x^n_pow2 - 1`, the vanishing polynomial of the padded trace domain.
+    pub(crate) fn z_trace<E: ExtensionField<F>>(&self, x: E) -> E {
+        x.pow(self.n_pow2 as u128) - E::one()
+    }
+
+    /// `prod_{i=first_exempt}^{n_pow2-1} (x - g^i)`, vanishing on the rows whose transitions are
+    /// not enforced. Linear in their number, so only for single points such as the verifier's `z`.
+    pub(crate) fn exemption<E: ExtensionField<F>>(&self, x: E) -> E {
+        let mut acc = E::one();
+        let mut p = self.g.pow(self.first_exempt as u128);
+        for _ in self.first_exempt..self.n_pow2 {
+            acc *= x - E::from(p);
+            p *= self.g;
+        }
+        acc
+    }
+
+    /// Coefficients of [`Self::exemption`], multiplied up a balanced product tree so the prover
+    /// can evaluate it over the whole LDE domain with one FFT.
+    pub(crate) fn exemption_coeffs(&self) -> Vec<F> {
+        let mut p = self.g.pow(self.first_exempt as u128);
+        let mut layer: Vec<Poly<F>> = (self.first_exempt..self.n_pow2)
+            .map(|_| {
+                let factor = Poly::new(vec![-p, F::one()]);
+                p *= self.g;
+                factor
+            })
+            .collect();
+        while layer.len() > 1 {
+            layer = layer
+                .chunks(2)
+                .map(|pair| match pair {
+                    [a, b] => a * b,
+                    [a] => a.clone(),
+                    _ => unreachable!(),
+                })
+                .collect();
+        }
+        layer.pop().map_or_else(|| vec![F::one()], |p| p.coeffs)
+    }
+
+    /// `x - g^row` for each assertion, the boundary denominators at `x`.
+    pub(crate) fn boundary_denominators<E: ExtensionField<F>>(&self, x: E) -> Vec<E> {
+        self.assertion_points.iter().map(|&p| x - E::from(p)).collect()
+    }
+
+    /// Composition value at `x` (outside the trace domain) from the trace rows at `x` and `x·g`,
+    /// with `exempt_over_z = exemption(x) / z_trace(x)` and `boundary_inv` the inverses of
+    /// [`Self::boundary_denominators`] at `x`. The first challenges weight the transition quotients
+    /// `C_j(x) · prod_exempt (x - g^i) / (x^N - 1)`, the rest the boundary quotients
+    /// `(t_col(x) - value) / (x - g^row)`. The prover evaluates at coset points in `F`, the
+    /// verifier at the out-of-domain point in the challenge field.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn eval<A: Air<Field = F>, E: ExtensionField<F>>(
+        &self,
+        exempt_over_z: E,
+        boundary_inv: &[E],
+        row: &[E],
+        next: &[E],
+        pub_inp: &A::PublicInput,
+        assertions: &[Assertion<F>],
+        alphas: &[Challenge<F>],
+    ) -> Challenge<F>
+    where
+        Challenge<F>: Mul<E, Output = Challenge<F>>,
+    {
+        let transitions = A::eval_transition(row, next, pub_inp);
+        debug_assert_eq!(transitions.len(), A::transition_degrees().len());
+        debug_assert_eq!(boundary_inv.len(), assertions.len());
+        assert_eq!(alphas.len(), transitions.len() + assertions.len(), "one challenge per constraint");
+        let (alphas_t, alphas_b) = alphas.split_at(transitions.len());
+        let t_sum = dot_product(alphas_t, &transitions);
+        let mut acc = t_sum * exempt_over_z;
+        for ((a, assertion), d_inv) in alphas_b.iter().zip(assertions).zip(boundary_inv) {
+            acc += *a * ((row[assertion.col] - E::from(assertion.value)) * *d_inv);
+        }
+        acc
+    }
+}
+
+/// Value at `x` of the DEEP composition `sum_i gamma_i (f_i(x) - f_i(s_i)) / (x - s_i)`, where the
+/// samples cover every trace column at `z`, every trace column at `z·g`, then the quotient and
+/// its mask at `z`.
+pub(crate) fn deep_eval<F: ProofField>(
+    x: F,
+    row: &[F],
+    quotient: Challenge<F>,
+    mask: Challenge<F>,
+    samples: &[DeepSample<Challenge<F>>],
+    gammas: &[Challenge<F>],
+) -> Challenge<F> {
+    let x = Challenge::<F>::from(x);
+    let row = row.iter().map(|&v| Challenge::<F>::from(v));
+    let values = row.clone().chain(row).chain([quotient, mask]);
+    let denoms: Vec<Challenge<F>> = samples.iter().map(|s| x - s.z).collect();
+    let terms: Vec<Challenge<F>> = values
+        .zip(samples)
+        .zip(batch_inverse(&denoms))
+        .map(|((v, s), d_inv)| (v - s.value) * d_inv)
+        .collect();
+    dot_product(&gammas[..terms.len()], &terms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use numiproof_air::FibonacciAir;
+    use numiproof_field::{Field, Fp};
+    use numiproof_poly::Radix2Domain;
+    use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+    #[test]
+    fn honest_quotient_is_low_degree() {
+        let air = FibonacciAir::new(1, 1, 20);
+        let pub_inp = air.public_input();
+        let assertions = <FibonacciAir>::assertions(&pub_inp);
+        let domain = ConstraintDomain::new(air.trace_len(), 32, &assertions);
+        let alphas: Vec<Challenge> =
+            (0..5u64).map(|i| <Challenge>::from_base_slice(&[Fp::new(3 + i), Fp::new(i), Fp::new(1 << i)])).collect();
+        let (trace_domain, lde_domain) = (Radix2Domain::new(32), Radix2Domain::new_coset(128, Fp::new(7)));
+        let cols: Vec<Vec<Fp>> = air
+            .gen_trace()
+            .into_iter()
+            .map(|mut c| {
+                let last = *c.last().unwrap();
+                c.resize(32, last);
+                lde_domain.evaluate(&trace_domain.interpolate(&c))
+            })
+            .collect();
+        let quotient: Vec<Challenge> = (0..128)
+            .map(|k| {
+                let x = lde_domain.element(k);
+                let row: Vec<Fp> = cols.iter().map(|c| c[k]).collect();
+                let next: Vec<Fp> = cols.iter().map(|c| c[(k + 4) % 128]).collect();
+                let boundary_inv = batch_inverse(&domain.boundary_denominators(x));
+                let exempt_over_z = domain.exemption(x) * domain.z_trace(x).inv();
+                domain.eval::<FibonacciAir, Fp>(exempt_over_z, &boundary_inv, &row, &next, &pub_inp, &assertions, &alphas)
+            })
+            .collect();
+        let quotient = lde_domain.interpolate(&quotient);
+        let bound = domain.degree_bound(1);
+        assert!(quotient[bound..].iter().all(|c| *c == <Challenge>::zero()));
+    }
+
+    #[test]
+    fn exemption_coefficients_match_pointwise_product() {
+        let mut rng = StdRng::seed_from_u64(5);
+        // Exempt runs of 1, 2, 9 and 17 rows (one past a power of two)
+        for n_rows in [32usize, 31, 24, 17] {
+            let domain = ConstraintDomain::<Fp>::new(n_rows, 64, &[]);
+            let coeffs = domain.exemption_coeffs();
+            assert_eq!(coeffs.len(), n_rows.next_power_of_two() - n_rows + 2);
+            for _ in 0..4 {
+                let x = Fp::new(rng.next_u64());
+                assert_eq!(Poly::new(coeffs.clone()).eval(x), domain.exemption(x));
+            }
+        }
+    }
+}
@@ -1,39 +1,83 @@
 // File: numiproof-proof/src/lib.rs
-use numiproof_air::{Air, row_to_bytes, FibPublic, FibonacciAir};
-use numiproof_hash::{h_many, shake256_384, Transcript, DOM_ROW, DOM_PROOF_DIGEST, DOM_ACCUMULATOR};
-use numiproof_merkle::MerkleTree;
-use rand::RngCore;
+use numiproof_air::Air;
+use numiproof_hash::{h_many, Transcript, DOM_PROOF_DIGEST, DOM_ACCUMULATOR};
 use serde::{Serialize, Deserialize};
-use numiproof_field::Fp;
-use numiproof_poly::{eval_poly_on_domain, vanishing_on_extended, lde_from_evals};
-use numiproof_fri::{FriProver, FriVerifier, FriCommitment, FriQuery, FriRoundCommitment, FriMultiCommitment, FriRoundQuery, FriMultiQuery};
+use numiproof_field::{
+    add_assign_slices, batch_inverse, batch_inverse_in_place, scale_slice, BabyBear, BabyBear4, ExtensionField, Field, Fp, Fp3,
+    PrimeField, TwoAdicField,
+};
+use rand::RngCore;
+use numiproof_poly::Radix2Domain;
+use numiproof_fri::{DeepCommitment, DeepSample, FriCommitment, FriProof, FriProver, FriVerifier, OracleCommitment, OracleProof};
+pub use numiproof_hash::{HashFunction, VerifyError, MAX_GRINDING_BITS};
 use rayon::prelude::*;
 
+mod composition;
+use composition::{deep_eval, ConstraintDomain};
+mod trace;
+pub use trace::{TraceCommitment, TraceQuery};
+
+/// Trace field of a proof together with the extension its challenges are drawn from. The
+/// challenge field holds every verifier challenge and everything derived from one: the
+/// out-of-domain point and samples, the quotient, its mask, the DEEP composition and all FRI
+/// layers. It needs about 2^128 elements or more for 128-bit soundness, which the trace field
+/// alone does not reach.
+pub trait ProofField: TwoAdicField {
+    type Challenge: ExtensionField<Self>;
+}
+
+/// Goldilocks challenges live in the cubic extension, about 2^192 elements.
+impl ProofField for Fp {
+    type Challenge = Fp3;
+}
+
+/// BabyBear challenges live in the quartic extension, about 2^124 elements.
+impl ProofField for BabyBear {
+    type Challenge = BabyBear4;
+}
+
+/// Challenge field of proofs over `F`; `Fp3` for the default Goldilocks field.
+pub type Challenge<F = Fp> = <F as ProofField>::Challenge;
+
+/// Opening of the committed trace LDE at a query point (index `idx` on the coset).
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Opening {
     pub idx: usize,
     pub row: Vec<u8>,
-    pub next_row: Option<Vec<u8>>,
+    pub salt: Vec<u8>,
     pub path_row: Vec<Vec<u8>>,
-    pub path_next: Option<Vec<Vec<u8>>>,
 }
 
+/// STARK proof over the trace field `F`.
 #[derive(Clone, Serialize, Deserialize)]
-pub struct ProofV1 {
+#[serde(bound = "")]
+pub struct ProofV1<F: ProofField = Fp> {
     pub version: u8,
     pub air_id: String,
+    /// Hash of every commitment and of the Fiat–Shamir transcript
+    pub hash: HashFunction,
     pub pub_input_enc: Vec<u8>,
+    /// Root over the trace LDE, one leaf per extended row
     pub merkle_root: Vec<u8>,
     pub n_rows: usize,
     pub n_cols: usize,
     pub queries: usize,
     pub openings: Vec<Opening>,
-    // FRI-oracle commitment to masked constraint-composition oracle over the extended domain
-    pub fri_commitment: Option<FriCommitment>,
-    pub fri_queries: Option<Vec<FriQuery>>, // legacy single-round
-    // Multi-round FRI (number of rounds configurable via FriConfig)
-    pub fri_rounds: Option<FriMultiCommitment>,
-    pub fri_round_queries: Option<Vec<FriMultiQuery>>,
+    /// log2 of the ratio between the extended (FRI) domain and the padded trace domain
+    pub blowup_log2: u32,
+    /// Constraint-quotient root plus out-of-domain samples: every trace column at `z`, every
+    /// trace column at `z·g`, then the quotient and its mask at `z`
+    pub deep: DeepCommitment<Challenge<F>>,
+    /// Quotient oracle openings at the query points
+    pub composition_openings: Vec<OracleProof<Challenge<F>>>,
+    /// Root of the prover-random composition mask oracle and its openings at the query points
+    pub mask_root: Vec<u8>,
+    pub mask_openings: Vec<OracleProof<Challenge<F>>>,
+    /// Low-degree proof for the DEEP composition over the LDE coset
+    pub fri_proof: FriProof<Challenge<F>>,
+    /// Leading zero bits required of the proof-of-work hash ahead of query sampling
+    pub grinding_bits: u32,
+    pub pow_nonce: u64,
     pub proof_digest: Vec<u8>,
 }
 
@@ -44,284 +88,375 @@ pub struct FriConfig {
     pub blowup_log2: u32,
     pub num_rounds: u32,
     pub queries: usize,
+    /// Backend for commitments and the transcript, recorded in the proof
+    pub hash: HashFunction,
+    /// Proof-of-work bits ground before the queries are drawn; each one doubles the cost of
+    /// retrying for favourable query positions, so fewer queries reach the same soundness
+    pub grinding_bits: u32,
 }
 impl Default for FriConfig {
     fn default() -> Self {
-        Self { blowup_log2: 3, num_rounds: 5, queries: 80 }
+        Self { blowup_log2: 3, num_rounds: 5, queries: 80, hash: HashFunction::default(), grinding_bits: 16 }
     }
 }
 
+/// Longest trace either side accepts. The verifier's work on the exempt padding rows grows with
+/// the claimed length, so a forged proof cannot make it arbitrarily expensive.
+pub const MAX_TRACE_ROWS: usize = 1 << 24;
+
+#[derive(Default)]
 pub struct Prover {
     pub cfg: FriConfig,
 }
-impl Default for Prover { fn default() -> Self { Self { cfg: FriConfig::default() } } }
 
-impl Prover {
-    pub fn prove_fib(&self, air: &FibonacciAir) -> ProofV1 {
-        let pub_inp = air.public_input();
-        let pub_inp_enc = bincode::serialize(&pub_inp).unwrap();
+/// Transcript prefix shared by prover and verifier: fields, statement, trace shape and trace root.
+fn statement_transcript<F: ProofField>(hash: HashFunction, air_id: &str, pub_inp_enc: &[u8], n_rows: usize, root: &[u8]) -> Transcript {
+    let mut tr = Transcript::with_hash("numiproof.fs", hash);
+    tr.absorb("field", &[F::MODULUS.to_le_bytes(), (Challenge::<F>::DEGREE as u64).to_le_bytes()].concat());
+    tr.absorb("air_id", air_id.as_bytes());
+    tr.absorb("pub_input", pub_inp_enc);
+    tr.absorb("n_rows", &(n_rows as u64).to_le_bytes());
+    tr.absorb("root", root);
+    tr
+}
 
-        // Build trace rows and leaves (base domain)
-        let cols = air.gen_trace();
-        let n = air.trace_len();
-        let mut leaves = Vec::with_capacity(n);
-        let mut rows = Vec::with_capacity(n);
-        (0..n).into_par_iter().for_each(|_i| {}); // ensure rayon linked
-        for i in 0..n {
-            let row: Vec<Fp> = vec![cols[0][i], cols[1][i]];
-            let bytes = row_to_bytes(&row);
-            let leaf = shake256_384(&h_many(DOM_ROW, &[&bytes])).to_vec();
-            leaves.push(leaf);
-            rows.push(bytes);
+/// Degree bound of the randomized trace polynomials `t(X) + (X^N - 1)·r(X)`: `r` has one more
+/// coefficient than the number of evaluations revealed per column (every query plus `z`, `z·g`).
+fn trace_degree(n_pow2: usize, queries: usize) -> Option<usize> {
+    n_pow2.checked_add(queries)?.checked_add(2)?.checked_next_power_of_two()
+}
+
+/// Draw the out-of-domain point, redrawing until neither `z` nor `z·g` lies in the trace domain
+/// (size `n_pow2`) or the LDE coset (size `ext_size`).
+fn draw_ood_point<F: ProofField>(tr: &mut Transcript, n_pow2: usize, ext_size: usize) -> Challenge<F> {
+    let shift_inv = F::coset_shift().inv();
+    loop {
+        let z: Challenge<F> = tr.challenge_ext("deep.z");
+        if z.pow(n_pow2 as u128) != Challenge::<F>::one() && (z * shift_inv).pow(ext_size as u128) != Challenge::<F>::one() {
+            return z;
         }
-        let mt = MerkleTree::build(&leaves);
-        let root = mt.root();
+    }
+}
+
+fn samples_to_bytes<F: ProofField>(samples: &[DeepSample<Challenge<F>>]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for c in samples.iter().flat_map(|s| s.value.base_coeffs()) {
+        c.write_bytes(&mut out);
+    }
+    out
+}
 
-        // Fiat–Shamir for queries
-        let mut tr = Transcript::new("numiproof.fs");
-        tr.absorb("air_id", air.id().as_bytes());
-        tr.absorb("pub_input", &pub_inp_enc);
-        tr.absorb("root", &root);
+/// Uniformly random element of the challenge field from prover-private randomness.
+fn random_challenge<F: ProofField, R: RngCore + ?Sized>(rng: &mut R) -> Challenge<F> {
+    let coeffs: Vec<F> = (0..Challenge::<F>::DEGREE).map(|_| F::from_u64(rng.next_u64())).collect();
+    Challenge::<F>::from_base_slice(&coeffs)
+}
 
-        // Build aggregated constraint composition on the base domain, then LDE-extend and ZK-mask
+impl Prover {
+    /// Prove any AIR: the trace, constraints and boundary conditions all come from `A`.
+    pub fn prove<A: Air>(&self, air: &A) -> ProofV1<A::Field>
+    where
+        A::Field: ProofField,
+    {
+        let pub_inp = air.public_input();
+        let pub_inp_enc = bincode::serialize(&pub_inp).unwrap();
+        let n = air.trace_len();
+        assert!(n >= 2, "trace needs at least two rows");
+        assert!(n <= MAX_TRACE_ROWS, "trace longer than MAX_TRACE_ROWS");
+        assert!(self.cfg.grinding_bits <= MAX_GRINDING_BITS, "grinding_bits above MAX_GRINDING_BITS");
+        let n_cols = A::n_cols();
         let blowup_log2 = self.cfg.blowup_log2;
+        let hash = self.cfg.hash;
         let base_pow2 = n.next_power_of_two();
-        let ext_size = base_pow2 << blowup_log2;
-
-        // Fiat–Shamir alphas for constraint aggregation
-        let alpha0_bytes = tr.challenge_bytes(8);
-        let alpha1_bytes = tr.challenge_bytes(8);
-        let alpha0 = Fp::new(u64::from_le_bytes(alpha0_bytes.try_into().unwrap()));
-        let alpha1 = Fp::new(u64::from_le_bytes(alpha1_bytes.try_into().unwrap()));
-
-        // Compute base-domain composition: sum_j alpha_j * c_j(i)
-        let mut comp_base: Vec<Fp> = vec![Fp::zero(); n];
-        for i in 0..n {
-            let row_fp = [cols[0][i], cols[1][i]];
-            let next_fp = if i + 1 < n { Some([cols[0][i+1], cols[1][i+1]]) } else { None };
-            let constraints = if let Some(nxt) = next_fp.as_ref() {
-                // Transition constraints for Fibonacci
-                let c0 = nxt[0] - row_fp[1];
-                let c1 = nxt[1] - (row_fp[0] + row_fp[1]);
-                [c0, c1]
-            } else {
-                // Last row boundary: a_i equals expected_first
-                let c_end = row_fp[0] - Fp::new(pub_inp.expected_first);
-                [c_end, Fp::zero()]
-            };
-            comp_base[i] = alpha0 * constraints[0] + alpha1 * constraints[1];
+        let trace_deg = trace_degree(base_pow2, self.cfg.queries).unwrap();
+        let ext_size = trace_deg << blowup_log2;
+        // Index distance between x and x·g on the coset
+        let row_step = ext_size / base_pow2;
+        // Prover-private randomness; never derived from the transcript
+        let mut rng = rand::thread_rng();
+
+        // Interpolate every column, padding the trace by repeating its last row, and add
+        // (X^N - 1)·r(X) for a random r so openings off the trace domain reveal nothing
+        let shift = A::Field::coset_shift();
+        // Twiddles for both domains are computed once and shared by every column
+        let trace_domain = Radix2Domain::new(base_pow2);
+        let lde_domain = Radix2Domain::new_coset(ext_size, shift);
+        let mut trace_coeffs: Vec<Vec<A::Field>> = air
+            .gen_trace()
+            .into_par_iter()
+            .map(|mut c| {
+                let last = *c.last().unwrap();
+                c.resize(base_pow2, last);
+                trace_domain.interpolate(&c)
+            })
+            .collect();
+        let n_random = self.cfg.queries + 2;
+        for c in trace_coeffs.iter_mut() {
+            c.resize(base_pow2 + n_random, A::Field::zero());
+            for i in 0..n_random {
+                let r = A::Field::from_u64(rng.next_u64());
+                c[i] -= r;
+                c[base_pow2 + i] += r;
+            }
         }
-        let comp_ext: Vec<Fp> = lde_from_evals(&comp_base, blowup_log2);
-
-        // ZK masking: add r(x) * z_base(x) so composition remains 0 on base points but hides values elsewhere
-        let mut rng_mask = tr.rng();
-        let r0 = Fp::new(rng_mask.next_u64());
-        let r1 = Fp::new(rng_mask.next_u64());
-        let mask_evals = {
-            let r_coeffs = [r0, r1];
-            let r_eval = eval_poly_on_domain(&r_coeffs, ext_size);
-            let z_base = vanishing_on_extended(ext_size, base_pow2);
-            r_eval.iter().zip(z_base.iter()).map(|(a,b)| *a * *b).collect::<Vec<Fp>>()
-        };
 
-        // Commit to masked composition oracle
-        let mut fri_values: Vec<Fp> = vec![Fp::zero(); ext_size];
-        for i in 0..ext_size {
-            fri_values[i] = comp_ext[i] + mask_evals[i];
-        }
-        let (fri_commitment, fri_mt) = FriProver::commit(&fri_values);
-        // Multi-round folding (configurable)
-        let mut fri_rounds: Vec<FriRoundCommitment> = Vec::new();
-        let mut round_mts: Vec<(Vec<Fp>, numiproof_merkle::MerkleTree)> = Vec::new();
-        let mut current_values = fri_values.clone();
-        for _round in 0..self.cfg.num_rounds {
-            let alpha_bytes = tr.challenge_bytes(8);
-            let alpha = Fp::new(u64::from_le_bytes(alpha_bytes.try_into().unwrap()));
-            let folded = numiproof_fri::FriProver::fold_values(alpha, &current_values);
-            let (rc, rmt) = numiproof_fri::FriProver::commit_round(&folded);
-            fri_rounds.push(rc);
-            round_mts.push((folded.clone(), rmt));
-            current_values = folded;
+        // LDE onto the coset and commit one salted leaf per extended row
+        let cols: Vec<Vec<A::Field>> = trace_coeffs.par_iter().map(|c| lde_domain.evaluate(c)).collect();
+        let trace = TraceCommitment::from_lde(&hash, cols, row_step, &mut rng);
+        let root = trace.root();
+
+        let mut tr = statement_transcript::<A::Field>(hash, A::id(), &pub_inp_enc, n, &root);
+
+        // One challenge per transition constraint and per boundary assertion
+        let assertions = A::assertions(&pub_inp);
+        let degrees = A::transition_degrees();
+        let alphas: Vec<Challenge<A::Field>> = tr.challenge_exts("alpha", degrees.len() + assertions.len());
+        let domain = ConstraintDomain::new(n, trace_deg, &assertions);
+        let degree_bound = domain.degree_bound(degrees.iter().copied().max().unwrap_or(1));
+        assert!(degree_bound <= ext_size, "blowup too small for the constraint degree");
+
+        // Quotient on the coset; x^N - 1 only takes `row_step` distinct values there, and the
+        // exemption polynomial is evaluated everywhere with one FFT
+        let z_denoms: Vec<A::Field> = (0..row_step).map(|j| domain.z_trace(lde_domain.element(j))).collect();
+        let z_inv = batch_inverse(&z_denoms);
+        let exempt = lde_domain.evaluate(&domain.exemption_coeffs());
+        let xs = lde_domain.elements();
+        // Boundary denominators for every point, inverted in independent batches
+        let n_assert = assertions.len();
+        let mut boundary_inv: Vec<A::Field> = xs.par_iter().flat_map_iter(|&x| domain.boundary_denominators(x)).collect();
+        boundary_inv.par_chunks_mut(n_assert.max(1) << 10).for_each(batch_inverse_in_place);
+        let quotient: Vec<Challenge<A::Field>> = (0..ext_size)
+            .into_par_iter()
+            .map(|k| {
+                let next = trace.row(trace.next_index(k));
+                let b_inv = &boundary_inv[k * n_assert..(k + 1) * n_assert];
+                domain.eval::<A, A::Field>(exempt[k] * z_inv[k % row_step], b_inv, &trace.row(k), &next, &pub_inp, &assertions, &alphas)
+            })
+            .collect();
+        let (quotient_commitment, quotient_mt) = FriProver::commit_salted(&hash, &quotient, &mut rng);
+        tr.absorb("composition.root", &quotient_commitment.oracle.root);
+
+        // Random mask of the composition's degree; it enters the DEEP composition so the FRI
+        // layers are uniformly masked
+        let mask_coeffs: Vec<Challenge<A::Field>> = (0..degree_bound).map(|_| random_challenge::<A::Field, _>(&mut rng)).collect();
+        let mask = lde_domain.evaluate(&mask_coeffs);
+        let (mask_commitment, mask_mt) = FriProver::commit_salted(&hash, &mask, &mut rng);
+        tr.absorb("mask.root", &mask_commitment.oracle.root);
+
+        // DEEP: sample the trace at z and z·g and the quotient and mask at z
+        let z = draw_ood_point::<A::Field>(&mut tr, base_pow2, ext_size);
+        let zg = z * A::Field::root_of_unity(base_pow2.trailing_zeros());
+        let mut quotient_coeffs = lde_domain.interpolate(&quotient);
+        quotient_coeffs.truncate(degree_bound);
+        let samples: Vec<DeepSample<Challenge<A::Field>>> = trace_coeffs
+            .iter()
+            .map(|c| FriProver::deep_sample(c, z))
+            .chain(trace_coeffs.iter().map(|c| FriProver::deep_sample(c, zg)))
+            .chain([FriProver::deep_sample(&quotient_coeffs, z), FriProver::deep_sample(&mask_coeffs, z)])
+            .collect();
+        tr.absorb("deep.samples", &samples_to_bytes::<A::Field>(&samples));
+
+        // DEEP composition: random combination of (f(X) - f(s)) / (X - s) over all samples
+        let gammas: Vec<Challenge<A::Field>> = tr.challenge_exts("deep.gamma", samples.len());
+        let mut deep_coeffs = vec![Challenge::<A::Field>::zero(); degree_bound];
+        let mut add_quotient = |mut q: Vec<Challenge<A::Field>>, gamma: Challenge<A::Field>| {
+            scale_slice(&mut q, gamma);
+            add_assign_slices(&mut deep_coeffs[..q.len()], &q);
+        };
+        let (trace_samples, own_samples) = samples.split_at(2 * n_cols);
+        let (trace_gammas, own_gammas) = gammas.split_at(2 * n_cols);
+        for ((poly, sample), &gamma) in trace_coeffs.iter().chain(&trace_coeffs).zip(trace_samples).zip(trace_gammas) {
+            add_quotient(FriProver::deep_quotient(poly, sample.z, sample.value), gamma);
         }
-
-        let mut rng = tr.rng();
-        let mut openings = Vec::with_capacity(self.cfg.queries);
-        let mut fri_queries: Vec<FriQuery> = Vec::with_capacity(self.cfg.queries);
-        let mut fri_round_queries: Vec<FriMultiQuery> = Vec::with_capacity(self.cfg.queries);
-        for _ in 0..self.cfg.queries {
-            let idx = (rng.next_u64() as usize) % n;
-            // open row i
-            let path_row = mt.open(idx);
-            let next_idx = if idx+1 < n { Some(idx+1) } else { None };
-            let (next_row, path_next) = if let Some(j) = next_idx {
-                (Some(rows[j].clone()), Some(mt.open(j)))
-            } else { (None, None) };
-            openings.push(Opening {
-                idx, row: rows[idx].clone(),
-                next_row,
-                path_row,
-                path_next,
-            });
-
-            // FRI-oracle opening at the mapped extended index for this base row
-            let ext_idx = idx << blowup_log2; // map base index to start of its coset in extended domain
-            let fp = fri_values[ext_idx];
-            let oracle_proof = FriProver::open(&fri_mt, ext_idx, fp);
-            fri_queries.push(FriQuery { oracle_proof });
-
-            // Pair openings for each folded round to check folding consistency
-            let mut rounds_vec = Vec::new();
-            for (folded_vals, rmt) in round_mts.iter() {
-                let pair = numiproof_fri::FriProver::open_pair(folded_vals, rmt, ext_idx % folded_vals.len());
-                rounds_vec.push(FriRoundQuery { pair });
-            }
-            fri_round_queries.push(FriMultiQuery { rounds: rounds_vec });
+        for ((poly, sample), &gamma) in [&quotient_coeffs, &mask_coeffs].into_iter().zip(own_samples).zip(own_gammas) {
+            add_quotient(FriProver::deep_quotient(poly, sample.z, sample.value), gamma);
         }
+        let deep_values = lde_domain.evaluate(&deep_coeffs);
+        let fri_layers = FriProver::commit_phase(&hash, deep_values, shift, degree_bound, self.cfg.num_rounds, &mut tr);
+        let pow_nonce = tr.grind(self.cfg.grinding_bits);
+
+        // Open the trace and the quotient at each query point, then the FRI layers. The samples
+        // at z·g stand in for the next row, so only the row at x is opened.
+        let query_idxs = tr.challenge_indices("query", self.cfg.queries, ext_size);
+        let openings = query_idxs.iter().map(|&idx| trace.open(idx)).collect();
+        let composition_openings = query_idxs
+            .iter()
+            .map(|&idx| FriProver::open(&quotient_mt, idx, quotient[idx]))
+            .collect();
+        let mask_openings = query_idxs.iter().map(|&idx| FriProver::open(&mask_mt, idx, mask[idx])).collect();
+        let fri_proof = FriProver::query_phase(&fri_layers, &query_idxs);
 
         let proof_digest = h_many(DOM_PROOF_DIGEST, &[&root, &pub_inp_enc, &(self.cfg.queries as u64).to_le_bytes()]).to_vec();
 
         ProofV1 {
             version: 1,
-            air_id: air.id().to_string(),
+            air_id: A::id().to_string(),
+            hash,
             pub_input_enc: pub_inp_enc,
             merkle_root: root,
             n_rows: n,
-            n_cols: air.n_cols(),
+            n_cols,
             queries: self.cfg.queries,
             openings,
-            fri_commitment: Some(fri_commitment),
-            fri_queries: Some(fri_queries),
-            fri_rounds: Some(FriMultiCommitment { rounds: fri_rounds }),
-            fri_round_queries: Some(fri_round_queries),
+            blowup_log2,
+            deep: DeepCommitment { samples, composition_root: quotient_commitment.oracle.root },
+            composition_openings,
+            mask_root: mask_commitment.oracle.root,
+            mask_openings,
+            fri_proof,
+            grinding_bits: self.cfg.grinding_bits,
+            pow_nonce,
             proof_digest,
         }
     }
 }
 
+/// Security policy the verifier enforces on its own, whatever the proof declares. The defaults
+/// are those of [`FriConfig::default`].
+#[derive(Clone, Copy)]
+pub struct VerifierConfig {
+    /// Fewest proof-of-work bits accepted; a proof may grind more, up to [`MAX_GRINDING_BITS`]
+    pub min_grinding_bits: u32,
+    pub min_queries: usize,
+    pub min_blowup_log2: u32,
+}
+impl Default for VerifierConfig {
+    fn default() -> Self {
+        let fri = FriConfig::default();
+        Self { min_grinding_bits: fri.grinding_bits, min_queries: fri.queries, min_blowup_log2: fri.blowup_log2 }
+    }
+}
+
 pub struct Verifier;
 impl Verifier {
-    pub fn verify_fib(proof: &ProofV1) -> bool {
-        if proof.version != 1 { return false; }
-        if proof.air_id != "fibonacci_v1" { return false; }
-        let Ok(pub_inp) = bincode::deserialize::<FibPublic>(&proof.pub_input_enc) else { return false; };
-        // Rebuild transcript to bind query positions
-        let mut tr = Transcript::new("numiproof.fs");
-        tr.absorb("air_id", proof.air_id.as_bytes());
-        tr.absorb("pub_input", &proof.pub_input_enc);
-        tr.absorb("root", &proof.merkle_root);
-        let mut rng = tr.rng();
-
-        for k in 0..proof.queries {
-            let expected_idx = (rng.next_u64() as usize) % proof.n_rows;
-            let o = &proof.openings[k];
-            if o.idx != expected_idx { return false; }
+    /// Verify a proof for AIR `A` under the default [`VerifierConfig`].
+    pub fn verify<A: Air>(proof: &ProofV1<A::Field>) -> Result<(), VerifyError>
+    where
+        A::Field: ProofField,
+    {
+        Self::verify_with::<A>(proof, &VerifierConfig::default())
+    }
 
-            // Verify Merkle openings
-            let leaf = shake256_384(&h_many(DOM_ROW, &[&o.row])).to_vec();
-            if !numiproof_merkle::MerkleTree::verify(&proof.merkle_root, o.idx, &leaf, &o.path_row) {
-                return false;
-            }
-            let row = match bytes_to_fps(&o.row) {
-                Some(r) => r,
-                None => return false,
-            };
-            let next = match (&o.next_row, &o.path_next) {
-                (Some(b), Some(path)) => {
-                    let j = o.idx + 1;
-                    let nleaf = shake256_384(&h_many(DOM_ROW, &[b])).to_vec();
-                    if !numiproof_merkle::MerkleTree::verify(&proof.merkle_root, j, &nleaf, path) {
-                        return false;
-                    }
-                    match bytes_to_fps(b) {
-                        Some(r) => Some(r),
-                        None => return false,
-                    }
-                },
-                (None, None) => None,
-                _ => return false
-            };
-            if !FibonacciAir::check_row(o.idx, &row, next.as_deref(), &pub_inp) {
-                return false;
-            }
+    /// Verify a proof for AIR `A`, using only the public input carried in the proof. Every
+    /// malformed or inconsistent proof is reported as an error; none makes the verifier panic.
+    pub fn verify_with<A: Air>(proof: &ProofV1<A::Field>, cfg: &VerifierConfig) -> Result<(), VerifyError>
+    where
+        A::Field: ProofField,
+    {
+        if proof.version != 1 { return Err(VerifyError::BadVersion { found: proof.version }); }
+        if proof.air_id != A::id() { return Err(VerifyError::UnknownAir { found: proof.air_id.clone() }); }
+        if proof.n_rows < 2 { return Err(VerifyError::MalformedEncoding("trace has fewer than two rows")); }
+        if proof.n_rows > MAX_TRACE_ROWS { return Err(VerifyError::MalformedEncoding("trace length")); }
+        if proof.n_cols != A::n_cols() { return Err(VerifyError::MalformedEncoding("column count")); }
+        if proof.grinding_bits < cfg.min_grinding_bits || proof.grinding_bits > MAX_GRINDING_BITS {
+            return Err(VerifyError::InvalidProofOfWork);
+        }
+        if proof.queries == 0
+            || proof.openings.len() != proof.queries
+            || proof.composition_openings.len() != proof.queries
+            || proof.mask_openings.len() != proof.queries
+        {
+            return Err(VerifyError::MalformedEncoding("query count"));
+        }
+        if proof.queries < cfg.min_queries { return Err(VerifyError::WeakParameters("queries")); }
+        if proof.blowup_log2 < cfg.min_blowup_log2 { return Err(VerifyError::WeakParameters("blowup")); }
+        if proof.deep.samples.len() != 2 * proof.n_cols + 2 {
+            return Err(VerifyError::MalformedEncoding("out-of-domain sample count"));
+        }
+        let pub_inp = bincode::deserialize::<A::PublicInput>(&proof.pub_input_enc)
+            .map_err(|_| VerifyError::MalformedEncoding("public input"))?;
+        if !A::check_public_input(&pub_inp, proof.n_rows) {
+            return Err(VerifyError::MalformedEncoding("public input"));
+        }
+        let assertions = A::assertions(&pub_inp);
+        if assertions.iter().any(|a| a.row >= proof.n_rows || a.col >= proof.n_cols) {
+            return Err(VerifyError::MalformedEncoding("assertion outside the trace"));
+        }
+        let base_pow2 = proof.n_rows.checked_next_power_of_two();
+        let trace_deg = base_pow2.and_then(|n| trace_degree(n, proof.queries));
+        let (Some(base_pow2), Some(trace_deg)) = (base_pow2, trace_deg) else {
+            return Err(VerifyError::MalformedEncoding("trace length"));
+        };
+        let max_log = A::Field::TWO_ADICITY;
+        if proof.blowup_log2 == 0 || proof.blowup_log2 > max_log || trace_deg.trailing_zeros() + proof.blowup_log2 > max_log {
+            return Err(VerifyError::MalformedEncoding("LDE domain size"));
+        }
+        let log_ext = trace_deg.trailing_zeros() + proof.blowup_log2;
+        let ext_size = 1usize << log_ext;
+
+        // Replay the transcript in exactly the prover's order
+        let shift = A::Field::coset_shift();
+        let hash = proof.hash;
+        let mut tr = statement_transcript::<A::Field>(hash, &proof.air_id, &proof.pub_input_enc, proof.n_rows, &proof.merkle_root);
+        let degrees = A::transition_degrees();
+        let alphas: Vec<Challenge<A::Field>> = tr.challenge_exts("alpha", degrees.len() + assertions.len());
+        let domain = ConstraintDomain::new(proof.n_rows, trace_deg, &assertions);
+        let degree_bound = domain.degree_bound(degrees.iter().copied().max().unwrap_or(1));
+        tr.absorb("composition.root", &proof.deep.composition_root);
+        tr.absorb("mask.root", &proof.mask_root);
+        let z = draw_ood_point::<A::Field>(&mut tr, base_pow2, ext_size);
+        let zg = z * A::Field::root_of_unity(base_pow2.trailing_zeros());
+
+        // The samples must sit at the drawn points and satisfy the AIR relation at z
+        let samples = &proof.deep.samples;
+        let (trace_z, rest) = samples.split_at(proof.n_cols);
+        let (trace_zg, quotient_z) = rest.split_at(proof.n_cols);
+        if trace_z.iter().chain(quotient_z).any(|s| s.z != z) || trace_zg.iter().any(|s| s.z != zg) {
+            return Err(VerifyError::MalformedEncoding("out-of-domain sample point"));
+        }
+        let row_z: Vec<Challenge<A::Field>> = trace_z.iter().map(|s| s.value).collect();
+        let row_zg: Vec<Challenge<A::Field>> = trace_zg.iter().map(|s| s.value).collect();
+        let exempt_over_z = domain.exemption(z) * domain.z_trace(z).inv();
+        let boundary_inv = batch_inverse(&domain.boundary_denominators(z));
+        if domain.eval::<A, Challenge<A::Field>>(exempt_over_z, &boundary_inv, &row_z, &row_zg, &pub_inp, &assertions, &alphas) != quotient_z[0].value {
+            let z = z.base_coeffs().iter().map(|c| c.as_canonical_u64()).collect();
+            return Err(VerifyError::ConstraintFailure { z });
+        }
+        tr.absorb("deep.samples", &samples_to_bytes::<A::Field>(samples));
+        let gammas: Vec<Challenge<A::Field>> = tr.challenge_exts("deep.gamma", samples.len());
+
+        let fri_alphas = FriVerifier::commit_challenges(&proof.fri_proof, ext_size, degree_bound, &mut tr)?;
+        if !tr.check_pow(proof.grinding_bits, proof.pow_nonce) {
+            return Err(VerifyError::InvalidProofOfWork);
+        }
+        let query_idxs = tr.challenge_indices("query", proof.queries, ext_size);
+        let deep_values = FriVerifier::verify_queries(&hash, &proof.fri_proof, &fri_alphas, shift, ext_size, &query_idxs)?;
+        let w = A::Field::root_of_unity(log_ext);
+        let quotient_commitment = FriCommitment { oracle: OracleCommitment { root: proof.deep.composition_root.clone(), len: ext_size, salted: true } };
+        let mask_commitment = FriCommitment { oracle: OracleCommitment { root: proof.mask_root.clone(), len: ext_size, salted: true } };
 
-            // Verify FRI oracle opening for same index and bind it to the opened row
-            if let (Some(ref commit), Some(ref queries)) = (&proof.fri_commitment, &proof.fri_queries) {
-                let q = &queries[k];
-                // Determine blowup from commitment length and base rows
-                let base_pow2 = proof.n_rows.next_power_of_two();
-                if commit.oracle.len % base_pow2 != 0 { return false; }
-                let ratio = commit.oracle.len / base_pow2;
-                if !ratio.is_power_of_two() { return false; }
-                let blowup_log2 = ratio.trailing_zeros() as usize;
-                let ext_idx = expected_idx << blowup_log2;
-                if q.oracle_proof.idx != ext_idx { return false; }
-                if !FriVerifier::verify_opening(commit, &q.oracle_proof) { return false; }
-
-                // Recompute transcript challenges and ZK mask exactly as the prover did
-                // to bind the FRI value to the constraint composition at this position
-                // Mask evaluations: r(x) * z_base(x) on extended domain
-                let ext_size = commit.oracle.len;
-                let mut rng_mask = tr.rng();
-                let r0 = Fp::new(rng_mask.next_u64());
-                let r1 = Fp::new(rng_mask.next_u64());
-                let r_coeffs = [r0, r1];
-                let r_eval = eval_poly_on_domain(&r_coeffs, ext_size);
-                let z_base = vanishing_on_extended(ext_size, base_pow2);
-                let mask_at_ext_idx = r_eval[ext_idx] * z_base[ext_idx];
-
-                // Constraint-composition challenges (match prover order)
-                let alpha0_bytes = tr.challenge_bytes(8);
-                let alpha1_bytes = tr.challenge_bytes(8);
-                let alpha0 = Fp::new(u64::from_le_bytes(alpha0_bytes.try_into().unwrap()));
-                let alpha1 = Fp::new(u64::from_le_bytes(alpha1_bytes.try_into().unwrap()));
-
-                // Evaluate constraints at this row (transition or boundary)
-                let (c0, c1) = if let Some(nxt) = next.as_ref() {
-                    // Transition constraints of Fibonacci AIR
-                    (nxt[0] - row[1], nxt[1] - (row[0] + row[1]))
-                } else {
-                    // Boundary constraint at last row
-                    (row[0] - Fp::new(pub_inp.expected_first), Fp::zero())
-                };
-                // At ext_idx corresponding to base i, LDE value equals base composition value
-                let expected_oracle_val = alpha0 * c0 + alpha1 * c1 + mask_at_ext_idx;
-                if q.oracle_proof.value != expected_oracle_val { return false; }
+        for (k, &expected_idx) in query_idxs.iter().enumerate() {
+            let o = &proof.openings[k];
+            let c = &proof.composition_openings[k];
+            let m = &proof.mask_openings[k];
+            if o.idx != expected_idx || c.idx != expected_idx || m.idx != expected_idx {
+                return Err(VerifyError::MerklePathMismatch { query: k });
             }
 
-            // Verify folding round inclusions (multi-round) with folding consistency checks
-            if let (Some(ref rounds), Some(ref rq)) = (&proof.fri_rounds, &proof.fri_round_queries) {
-                let num_rounds = rounds.rounds.len();
-                if rq[k].rounds.len() != num_rounds { return false; }
-                for r_i in 0..num_rounds {
-                    // derive per-round alpha to match prover's sequence
-                    let alpha_bytes = tr.challenge_bytes(8);
-                    let alpha = Fp::new(u64::from_le_bytes(alpha_bytes.try_into().unwrap()));
-                    let r = &rounds.rounds[r_i];
-                    let q = &rq[k].rounds[r_i];
-                    // Verify Merkle inclusion for this round
-                    if !numiproof_fri::FriVerifier::verify_pair(&r.root, r.len, &q.pair) { return false; }
-                    // Verify folding consistency between consecutive rounds
-                    let next_pair = if r_i + 1 < num_rounds {
-                        Some(&rq[k].rounds[r_i + 1].pair)
-                    } else {
-                        None
-                    };
-                    if !numiproof_fri::FriVerifier::verify_folding_chain(alpha, &q.pair, next_pair) {
-                        return false;
-                    }
-                }
-                // Final round should be smaller than initial (folding is working) when there are multiple rounds
-                if num_rounds > 1 {
-                    if let Some((first_round, last_round)) = rounds.rounds.first().zip(rounds.rounds.last()) {
-                        if last_round.len >= first_round.len { return false; }
-                    }
-                }
+            // Verify Merkle openings of the trace row, the quotient and the mask at x
+            let row = TraceCommitment::<A::Field>::verify(&hash, &proof.merkle_root, proof.n_cols, ext_size, o).map_err(at_query(k))?;
+            FriVerifier::verify_opening(&hash, &quotient_commitment, c).map_err(at_query(k))?;
+            FriVerifier::verify_opening(&hash, &mask_commitment, m).map_err(at_query(k))?;
+
+            // The DEEP composition at x must match the first FRI layer
+            let x = shift * w.pow(o.idx as u128);
+            if deep_eval::<A::Field>(x, &row, c.value, m.value, samples, &gammas) != deep_values[k] {
+                return Err(VerifyError::DeepMismatch { query: k });
             }
         }
 
         // Final digest check binds root, public input, and query count
         let expect_digest = h_many(DOM_PROOF_DIGEST, &[&proof.merkle_root, &proof.pub_input_enc, &(proof.queries as u64).to_le_bytes()]);
-        proof.proof_digest == expect_digest
+        if proof.proof_digest != expect_digest { return Err(VerifyError::DigestMismatch); }
+        Ok(())
+    }
+}
+
+/// Report a failed standalone opening as a Merkle failure of query `k`.
+fn at_query(k: usize) -> impl Fn(VerifyError) -> VerifyError {
+    move |e| match e {
+        VerifyError::MerklePathMismatch { .. } => VerifyError::MerklePathMismatch { query: k },
+        e => e,
     }
 }
 
@@ -331,11 +466,8 @@ impl Verifier {
 pub fn digest_to_fps(digest: &[u8]) -> Option<[Fp; 6]> {
     if digest.len() != numiproof_hash::DIGEST_LEN { return None; }
     let mut limbs = [Fp::zero(); 6];
-    for i in 0..6 {
-        let start = i*8;
-        let mut chunk = [0u8; 8];
-        chunk.copy_from_slice(&digest[start..start+8]);
-        limbs[i] = Fp::new(u64::from_le_bytes(chunk));
+    for (limb, chunk) in limbs.iter_mut().zip(digest.chunks_exact(8)) {
+        *limb = Fp::new(u64::from_le_bytes(chunk.try_into().unwrap()));
     }
     Some(limbs)
 }
@@ -348,24 +480,22 @@ pub fn fps_to_digest(limbs: &[Fp; 6]) -> Vec<u8> {
 }
 
 /// Re-export Merkle inclusion verification in a gadget-friendly signature.
-pub fn merkle_verify_root(root: &[u8], idx: usize, leaf: &[u8], path: &[Vec<u8>]) -> bool {
-    numiproof_merkle::MerkleTree::verify(root, idx, leaf, path)
+pub fn merkle_verify_root(hash: HashFunction, root: &[u8], idx: usize, leaf: &[u8], path: &[Vec<u8>]) -> bool {
+    numiproof_merkle::MerkleTree::verify(&hash, root, idx, leaf, path).is_ok()
 }
 
 /// Re-export FRI pair inclusion verification in a gadget-friendly signature.
-pub fn fri_verify_pair(root: &[u8], len: usize, pair: &numiproof_fri::PairOpening) -> bool {
-    numiproof_fri::FriVerifier::verify_pair(root, len, pair)
+pub fn fri_verify_pair(hash: HashFunction, root: &[u8], len: usize, pair: &numiproof_fri::PairOpening) -> bool {
+    numiproof_fri::FriVerifier::verify_pair(&hash, root, len, pair).is_ok()
 }
 
 /// Compute accumulator digest used for recursion pipeline.
 pub fn accumulator_digest(prev: Option<&[u8]>, cur: &[u8]) -> Vec<u8> { accumulate(prev, cur) }
 
-fn bytes_to_fps(b: &[u8]) -> Option<Vec<Fp>> {
-    if b.len()%8!=0 { return None; }
-    Some(b.chunks_exact(8).map(|c| {
-        let v = u64::from_le_bytes(c.try_into().unwrap());
-        Fp::new(v)
-    }).collect())
+/// Strictly decode a row of canonical field elements; any value `>= MODULUS` rejects the row.
+fn bytes_to_fps<F: PrimeField>(b: &[u8]) -> Option<Vec<F>> {
+    if !b.len().is_multiple_of(F::NUM_BYTES) { return None; }
+    b.chunks_exact(F::NUM_BYTES).map(F::from_canonical_bytes).collect()
 }
 
 /// Hash-chain accumulator for "recursive" aggregation of proofs.
@@ -379,71 +509,335 @@ pub fn accumulate(prev: Option<&[u8]>, cur: &[u8]) -> Vec<u8> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use numiproof_field::root_of_unity;
+    use numiproof_air::FibonacciAir;
+    use numiproof_air::examples::{HashChainAir, PermutationAir, Poseidon2Air, RangeCheckAir};
+
+    /// Default proof-of-work floor, but small enough query and blowup minimums for the test
+    /// parameters.
+    const TEST_POLICY: VerifierConfig = VerifierConfig { min_grinding_bits: 16, min_queries: 1, min_blowup_log2: 1 };
+
     #[test]
     fn fib_prove_verify() {
         let air = FibonacciAir::new(1,1,64);
-        let prover = Prover { cfg: FriConfig { blowup_log2: 2, num_rounds: 1, queries: 32 } };
-        let proof = prover.prove_fib(&air);
-        assert!(Verifier::verify_fib(&proof));
+        let prover = Prover { cfg: FriConfig { blowup_log2: 2, num_rounds: 1, queries: 32, ..Default::default() } };
+        let proof = prover.prove(&air);
+        assert!(Verifier::verify_with::<FibonacciAir>(&proof, &TEST_POLICY).is_ok());
+    }
+
+    #[test]
+    fn every_hash_backend_proves_and_is_bound() {
+        let air = FibonacciAir::new(1, 1, 32);
+        for hash in HashFunction::ALL {
+            let prover = Prover { cfg: FriConfig { blowup_log2: 2, num_rounds: 2, queries: 8, hash, ..Default::default() } };
+            let proof = prover.prove(&air);
+            assert_eq!(proof.hash, hash);
+            assert_eq!(proof.merkle_root.len(), numiproof_hash::MerkleHasher::digest_len(&hash));
+            assert!(Verifier::verify_with::<FibonacciAir>(&proof, &TEST_POLICY).is_ok());
+            // Relabelling the header breaks the commitments and the transcript
+            let mut relabelled = proof.clone();
+            relabelled.hash = HashFunction::ALL[(hash as usize + 1) % HashFunction::ALL.len()];
+            assert!(Verifier::verify_with::<FibonacciAir>(&relabelled, &TEST_POLICY).is_err());
+        }
+    }
+
+    #[test]
+    fn babybear_fib_prove_verify() {
+        let air = FibonacciAir::<BabyBear>::in_field(1, 1, 64);
+        let prover = Prover { cfg: FriConfig { blowup_log2: 2, num_rounds: 2, queries: 16, ..Default::default() } };
+        let proof = prover.prove(&air);
+        assert!(Verifier::verify_with::<FibonacciAir<BabyBear>>(&proof, &TEST_POLICY).is_ok());
+        assert_eq!(proof.openings[0].row.len(), 2 * BabyBear::NUM_BYTES);
+        let mut bad = proof.clone();
+        bad.deep.samples[2].value += BabyBear4::one();
+        assert!(matches!(Verifier::verify_with::<FibonacciAir<BabyBear>>(&bad, &TEST_POLICY), Err(VerifyError::ConstraintFailure { .. })));
+        // The same AIR over Goldilocks does not accept it, whatever the decoder makes of the bytes
+        let bytes = bincode::serialize(&proof).unwrap();
+        if let Ok(p) = bincode::deserialize::<ProofV1>(&bytes) {
+            assert!(Verifier::verify_with::<FibonacciAir>(&p, &TEST_POLICY).is_err());
+        }
+    }
+
+    #[test]
+    fn example_airs_prove_verify() {
+        let prover = Prover { cfg: FriConfig { blowup_log2: 2, num_rounds: 1, queries: 16, ..Default::default() } };
+        let proof = prover.prove(&RangeCheckAir::new(42, 8));
+        assert!(Verifier::verify_with::<RangeCheckAir>(&proof, &TEST_POLICY).is_ok());
+        let proof = prover.prove(&PermutationAir::new(vec![1, 2, 3, 4, 5], vec![5, 3, 1, 4, 2]));
+        assert!(Verifier::verify_with::<PermutationAir>(&proof, &TEST_POLICY).is_ok());
+    }
+
+    #[test]
+    fn hash_chain_fails_low_degree_test() {
+        // SHAKE transitions are not polynomial, so the quotient is far from low degree
+        let prover = Prover { cfg: FriConfig { blowup_log2: 2, num_rounds: 1, queries: 16, ..Default::default() } };
+        let proof = prover.prove(&HashChainAir::new(vec![1, 2, 3, 4], 3));
+        assert!(Verifier::verify_with::<HashChainAir>(&proof, &TEST_POLICY).is_err());
+    }
+
+    #[test]
+    fn poseidon2_chain_prove_verify() {
+        // Unlike the SHAKE chain, the Poseidon2 constraints are polynomials and the proof verifies
+        let prover = Prover { cfg: FriConfig { blowup_log2: 2, num_rounds: 2, queries: 8, ..Default::default() } };
+        let proof = prover.prove(&Poseidon2Air::new([1, 2, 3, 4, 5, 6, 7, 8], 3));
+        assert!(Verifier::verify_with::<Poseidon2Air>(&proof, &TEST_POLICY).is_ok());
+        let mut bad = proof.clone();
+        bad.deep.samples[20].value += Fp3::one();
+        assert!(matches!(Verifier::verify_with::<Poseidon2Air>(&bad, &TEST_POLICY), Err(VerifyError::ConstraintFailure { .. })));
+    }
+
+    /// Fibonacci AIR whose witness breaks one transition in the middle of the trace.
+    struct BrokenFib(FibonacciAir);
+    impl Air for BrokenFib {
+        type Field = Fp;
+        type PublicInput = <FibonacciAir as Air>::PublicInput;
+        fn id() -> &'static str { <FibonacciAir>::id() }
+        fn n_cols() -> usize { <FibonacciAir>::n_cols() }
+        fn trace_len(&self) -> usize { self.0.trace_len() }
+        fn public_input(&self) -> Self::PublicInput { self.0.public_input() }
+        fn gen_trace(&self) -> Vec<Vec<Fp>> {
+            let mut cols = self.0.gen_trace();
+            cols[1][10] += Fp::one();
+            cols
+        }
+        fn transition_degrees() -> Vec<usize> { <FibonacciAir>::transition_degrees() }
+        fn eval_transition<E: ExtensionField>(row: &[E], next: &[E], pub_inp: &Self::PublicInput) -> Vec<E> {
+            <FibonacciAir>::eval_transition(row, next, pub_inp)
+        }
+        fn assertions(pub_inp: &Self::PublicInput) -> Vec<numiproof_air::Assertion> {
+            <FibonacciAir>::assertions(pub_inp)
+        }
+    }
+
+    #[test]
+    fn verify_rejects_unsatisfied_trace() {
+        let prover = Prover { cfg: FriConfig { blowup_log2: 2, num_rounds: 2, queries: 16, ..Default::default() } };
+        let proof = prover.prove(&BrokenFib(FibonacciAir::new(1, 1, 32)));
+        assert!(Verifier::verify_with::<FibonacciAir>(&proof, &TEST_POLICY).is_err());
+    }
+
+    #[test]
+    fn proofs_are_randomized_but_verify() {
+        let air = FibonacciAir::new(1,1,16);
+        let prover = Prover { cfg: FriConfig { blowup_log2: 2, num_rounds: 2, queries: 8, ..Default::default() } };
+        let a = prover.prove(&air);
+        let b = prover.prove(&air);
+        assert!(Verifier::verify_with::<FibonacciAir>(&a, &TEST_POLICY).is_ok());
+        assert!(Verifier::verify_with::<FibonacciAir>(&b, &TEST_POLICY).is_ok());
+        assert_ne!(bincode::serialize(&a).unwrap(), bincode::serialize(&b).unwrap());
+        assert_ne!(a.merkle_root, b.merkle_root);
+        assert_ne!(a.deep.samples[0].value, b.deep.samples[0].value);
+    }
+
+    #[test]
+    fn queries_open_only_points_off_the_trace_domain() {
+        let air = FibonacciAir::new(1,1,16);
+        let prover = Prover { cfg: FriConfig { blowup_log2: 2, num_rounds: 1, queries: 16, ..Default::default() } };
+        let proof = prover.prove(&air);
+        let n_pow2 = proof.n_rows.next_power_of_two();
+        let ext_size = trace_degree(n_pow2, proof.queries).unwrap() << proof.blowup_log2;
+        let w = root_of_unity(ext_size.trailing_zeros());
+        for o in &proof.openings {
+            let x = Fp::coset_shift() * w.pow(o.idx as u128);
+            assert_ne!(x.pow(n_pow2 as u128), Fp::one());
+        }
+        let z = proof.deep.samples[0].z;
+        assert_ne!(z.pow(n_pow2 as u128), <Challenge>::one());
+        // The out-of-domain point is drawn from the extension, not from Fp
+        assert!(z.base_coeffs()[1..].iter().any(|c| *c != Fp::zero()));
+    }
+
+    #[test]
+    fn verify_rejects_wrong_air() {
+        let air = FibonacciAir::new(1,1,16);
+        let prover = Prover { cfg: FriConfig { blowup_log2: 2, num_rounds: 1, queries: 8, ..Default::default() } };
+        let proof = prover.prove(&air);
+        assert!(matches!(Verifier::verify_with::<RangeCheckAir>(&proof, &TEST_POLICY), Err(VerifyError::UnknownAir { .. })));
+    }
+
+    #[test]
+    fn verify_rejects_truncated_openings() {
+        let air = FibonacciAir::new(1,1,16);
+        let prover = Prover { cfg: FriConfig { blowup_log2: 2, num_rounds: 1, queries: 8, ..Default::default() } };
+        let mut proof = prover.prove(&air);
+        proof.openings.pop();
+        assert_eq!(Verifier::verify_with::<FibonacciAir>(&proof, &TEST_POLICY), Err(VerifyError::MalformedEncoding("query count")));
     }
 
     #[test]
     fn verify_rejects_tampered_row() {
         let air = FibonacciAir::new(1,1,32);
-        let prover = Prover { cfg: FriConfig { blowup_log2: 2, num_rounds: 1, queries: 16 } };
-        let mut proof = prover.prove_fib(&air);
+        let prover = Prover { cfg: FriConfig { blowup_log2: 2, num_rounds: 1, queries: 16, ..Default::default() } };
+        let mut proof = prover.prove(&air);
         // Tamper a byte in first opening row; proof should fail
         if let Some(first) = proof.openings.get_mut(0) {
             if !first.row.is_empty() { first.row[0] ^= 1; }
         }
 
-        assert!(!Verifier::verify_fib(&proof));
+        assert_eq!(Verifier::verify_with::<FibonacciAir>(&proof, &TEST_POLICY), Err(VerifyError::MerklePathMismatch { query: 0 }));
     }
 
     #[test]
     fn verify_rejects_wrong_query_index() {
         let air = FibonacciAir::new(1,1,32);
-        let prover = Prover { cfg: FriConfig { blowup_log2: 2, num_rounds: 1, queries: 16 } };
-        let mut proof = prover.prove_fib(&air);
+        let prover = Prover { cfg: FriConfig { blowup_log2: 2, num_rounds: 1, queries: 16, ..Default::default() } };
+        let mut proof = prover.prove(&air);
         // Force an incorrect index for first opening
         if let Some(first) = proof.openings.get_mut(0) { first.idx = (first.idx + 1) % proof.n_rows; }
-        assert!(!Verifier::verify_fib(&proof));
+        assert_eq!(Verifier::verify_with::<FibonacciAir>(&proof, &TEST_POLICY), Err(VerifyError::MerklePathMismatch { query: 0 }));
+    }
+
+    #[test]
+    fn verify_rejects_bad_row_path() {
+        let air = FibonacciAir::new(1,1,32);
+        let prover = Prover { cfg: FriConfig { blowup_log2: 2, num_rounds: 1, queries: 16, ..Default::default() } };
+        let mut proof = prover.prove(&air);
+        // Tamper the row's Merkle path
+        proof.openings[0].path_row[0][0] ^= 1;
+        assert_eq!(Verifier::verify_with::<FibonacciAir>(&proof, &TEST_POLICY), Err(VerifyError::MerklePathMismatch { query: 0 }));
     }
 
     #[test]
-    fn verify_rejects_bad_next_row_path() {
+    fn verify_rejects_tampered_ood_samples() {
         let air = FibonacciAir::new(1,1,32);
-        let prover = Prover { cfg: FriConfig { blowup_log2: 2, num_rounds: 1, queries: 16 } };
-        let mut proof = prover.prove_fib(&air);
-        // Tamper next_row path on an opening that has a next_row
-        let k = proof.openings.iter().position(|o| o.next_row.is_some()).unwrap();
-        if let Some(path_next) = &mut proof.openings[k].path_next { if let Some(first) = path_next.first_mut() { if !first.is_empty() { first[0] ^= 1; } } }
-        assert!(!Verifier::verify_fib(&proof));
+        let prover = Prover { cfg: FriConfig { blowup_log2: 2, num_rounds: 2, queries: 8, ..Default::default() } };
+        let proof = prover.prove(&air);
+        // Trace value at z·g no longer satisfies the AIR relation at z
+        let mut bad = proof.clone();
+        bad.deep.samples[2].value += <Challenge>::one();
+        let z = proof.deep.samples[0].z.base_coeffs().iter().map(|c| c.as_canonical_u64()).collect();
+        assert_eq!(Verifier::verify_with::<FibonacciAir>(&bad, &TEST_POLICY), Err(VerifyError::ConstraintFailure { z }));
+        // Sample moved to a different point
+        let mut bad = proof.clone();
+        bad.deep.samples[0].z += <Challenge>::one();
+        assert!(matches!(Verifier::verify_with::<FibonacciAir>(&bad, &TEST_POLICY), Err(VerifyError::MalformedEncoding(_))));
+        // Composition and mask openings inconsistent with their commitments
+        let mut bad = proof.clone();
+        bad.composition_openings[0].value += <Challenge>::one();
+        assert_eq!(Verifier::verify_with::<FibonacciAir>(&bad, &TEST_POLICY), Err(VerifyError::MerklePathMismatch { query: 0 }));
+        let mut bad = proof.clone();
+        bad.mask_openings[0].value += <Challenge>::one();
+        assert_eq!(Verifier::verify_with::<FibonacciAir>(&bad, &TEST_POLICY), Err(VerifyError::MerklePathMismatch { query: 0 }));
+        // Salt swapped out
+        let mut bad = proof;
+        bad.openings[0].salt[0] ^= 1;
+        assert_eq!(Verifier::verify_with::<FibonacciAir>(&bad, &TEST_POLICY), Err(VerifyError::MerklePathMismatch { query: 0 }));
     }
 
     #[test]
     fn verify_rejects_pub_input_mismatch() {
         let air = FibonacciAir::new(2,3,16);
-        let prover = Prover { cfg: FriConfig { blowup_log2: 2, num_rounds: 1, queries: 8 } };
-        let mut proof = prover.prove_fib(&air);
+        let prover = Prover { cfg: FriConfig { blowup_log2: 2, num_rounds: 1, queries: 8, ..Default::default() } };
+        let mut proof = prover.prove(&air);
         // Flip a byte in public input encoding
         if !proof.pub_input_enc.is_empty() { proof.pub_input_enc[0] ^= 1; }
-        assert!(!Verifier::verify_fib(&proof));
+        assert!(Verifier::verify_with::<FibonacciAir>(&proof, &TEST_POLICY).is_err());
     }
 
     #[test]
-    #[ignore]
     fn fri_binding_rejects_tampered_value() {
         let air = FibonacciAir::new(1,1,32);
-        let prover = Prover { cfg: FriConfig { blowup_log2: 2, num_rounds: 1, queries: 8 } };
-        let mut proof = prover.prove_fib(&air);
-        // Tamper the first FRI oracle value
-        if let Some(ref mut fri_queries) = proof.fri_queries {
-            if let Some(first) = fri_queries.first_mut() {
-                first.oracle_proof.value = first.oracle_proof.value + Fp::one();
-            }
-        }
-        // Deliberately assert success; this should FAIL because verifier returns false
-        assert!(Verifier::verify_fib(&proof));
+        let prover = Prover { cfg: FriConfig { blowup_log2: 2, num_rounds: 1, queries: 8, ..Default::default() } };
+        let mut proof = prover.prove(&air);
+        // Tamper the first layer-0 FRI value
+        proof.fri_proof.queries[0].rounds[0].pair.lo += <Challenge>::one();
+        assert_eq!(Verifier::verify_with::<FibonacciAir>(&proof, &TEST_POLICY), Err(VerifyError::MerklePathMismatch { query: 0 }));
+    }
+
+    #[test]
+    fn verify_rejects_tampered_final_poly() {
+        let air = FibonacciAir::new(1,1,32);
+        let prover = Prover { cfg: FriConfig { blowup_log2: 2, num_rounds: 3, queries: 8, ..Default::default() } };
+        let mut proof = prover.prove(&air);
+        assert!(Verifier::verify_with::<FibonacciAir>(&proof, &TEST_POLICY).is_ok());
+        proof.fri_proof.final_poly[0] += <Challenge>::one();
+        // The final polynomial is absorbed before the grinding, so the nonce no longer holds
+        assert_eq!(Verifier::verify_with::<FibonacciAir>(&proof, &TEST_POLICY), Err(VerifyError::InvalidProofOfWork));
+        // Without grinding the queries move instead
+        let prover = Prover { cfg: FriConfig { grinding_bits: 0, ..prover.cfg } };
+        let mut proof = prover.prove(&air);
+        proof.fri_proof.final_poly[0] += <Challenge>::one();
+        let cfg = VerifierConfig { min_grinding_bits: 0, ..TEST_POLICY };
+        assert_eq!(Verifier::verify_with::<FibonacciAir>(&proof, &cfg), Err(VerifyError::MerklePathMismatch { query: 0 }));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn verify_checks_proof_of_work_before_queries() {
+        let air = FibonacciAir::new(1,1,32);
+        let prover = Prover { cfg: FriConfig { blowup_log2: 2, num_rounds: 1, queries: 8, grinding_bits: 12, ..Default::default() } };
+        let proof = prover.prove(&air);
+        assert_eq!(proof.grinding_bits, 12);
+        let cfg = VerifierConfig { min_grinding_bits: 12, ..TEST_POLICY };
+        assert!(Verifier::verify_with::<FibonacciAir>(&proof, &cfg).is_ok());
+        // Below the default minimum of 16 bits
+        assert_eq!(Verifier::verify_with::<FibonacciAir>(&proof, &TEST_POLICY), Err(VerifyError::InvalidProofOfWork));
+        let mut bad = proof.clone();
+        // `grind` returns the smallest valid nonce, so the one before it fails
+        bad.pow_nonce = proof.pow_nonce.wrapping_sub(1);
+        assert_eq!(Verifier::verify_with::<FibonacciAir>(&bad, &cfg), Err(VerifyError::InvalidProofOfWork));
+        // The difficulty is hashed with the nonce, so raising it invalidates the nonce
+        let mut bad = proof.clone();
+        bad.grinding_bits = 30;
+        assert_eq!(Verifier::verify_with::<FibonacciAir>(&bad, &cfg), Err(VerifyError::InvalidProofOfWork));
+        let mut bad = proof;
+        bad.grinding_bits = MAX_GRINDING_BITS + 1;
+        assert_eq!(Verifier::verify_with::<FibonacciAir>(&bad, &cfg), Err(VerifyError::InvalidProofOfWork));
+    }
+
+    #[test]
+    fn default_verifier_rejects_ungrinded_proofs() {
+        let air = FibonacciAir::new(1,1,32);
+        let prover = Prover { cfg: FriConfig { grinding_bits: 0, ..Default::default() } };
+        let proof = prover.prove(&air);
+        assert_eq!(Verifier::verify::<FibonacciAir>(&proof), Err(VerifyError::InvalidProofOfWork));
+        let cfg = VerifierConfig { min_grinding_bits: 0, ..Default::default() };
+        assert!(Verifier::verify_with::<FibonacciAir>(&proof, &cfg).is_ok());
+    }
+
+    #[test]
+    fn default_verifier_rejects_weak_parameters() {
+        let air = FibonacciAir::new(1,1,32);
+        assert!(Verifier::verify::<FibonacciAir>(&Prover::default().prove(&air)).is_ok());
+        // One query over a 2x blowup passes every structural check
+        let prover = Prover { cfg: FriConfig { blowup_log2: 1, num_rounds: 1, queries: 1, ..Default::default() } };
+        let proof = prover.prove(&air);
+        assert!(Verifier::verify_with::<FibonacciAir>(&proof, &TEST_POLICY).is_ok());
+        assert_eq!(Verifier::verify::<FibonacciAir>(&proof), Err(VerifyError::WeakParameters("queries")));
+        let prover = Prover { cfg: FriConfig { blowup_log2: 2, ..Default::default() } };
+        assert_eq!(Verifier::verify::<FibonacciAir>(&prover.prove(&air)), Err(VerifyError::WeakParameters("blowup")));
+    }
+
+    #[test]
+    fn verify_reports_malformed_proofs_without_panicking() {
+        let air = FibonacciAir::new(1,1,16);
+        let prover = Prover { cfg: FriConfig { blowup_log2: 2, num_rounds: 1, queries: 8, ..Default::default() } };
+        let proof = prover.prove(&air);
+        let mut bad = proof.clone();
+        bad.version = 2;
+        assert_eq!(Verifier::verify_with::<FibonacciAir>(&bad, &TEST_POLICY), Err(VerifyError::BadVersion { found: 2 }));
+        let mut bad = proof.clone();
+        bad.blowup_log2 = u32::MAX;
+        assert!(matches!(Verifier::verify_with::<FibonacciAir>(&bad, &TEST_POLICY), Err(VerifyError::MalformedEncoding(_))));
+        let mut bad = proof.clone();
+        bad.n_rows = usize::MAX;
+        assert!(matches!(Verifier::verify_with::<FibonacciAir>(&bad, &TEST_POLICY), Err(VerifyError::MalformedEncoding(_))));
+        let mut bad = proof.clone();
+        bad.n_rows = MAX_TRACE_ROWS + 1;
+        assert_eq!(Verifier::verify_with::<FibonacciAir>(&bad, &TEST_POLICY), Err(VerifyError::MalformedEncoding("trace length")));
+        let mut bad = proof.clone();
+        bad.deep.samples.clear();
+        assert!(matches!(Verifier::verify_with::<FibonacciAir>(&bad, &TEST_POLICY), Err(VerifyError::MalformedEncoding(_))));
+        let mut bad = proof.clone();
+        bad.openings[0].row.pop();
+        assert!(Verifier::verify_with::<FibonacciAir>(&bad, &TEST_POLICY).is_err());
+        let mut bad = proof.clone();
+        bad.openings[0].path_row.clear();
+        assert_eq!(Verifier::verify_with::<FibonacciAir>(&bad, &TEST_POLICY), Err(VerifyError::MalformedEncoding("trace path")));
+        let mut bad = proof.clone();
+        bad.fri_proof.queries[3].rounds.clear();
+        assert!(matches!(Verifier::verify_with::<FibonacciAir>(&bad, &TEST_POLICY), Err(VerifyError::MalformedEncoding(_))));
+        let mut bad = proof;
+        bad.proof_digest[0] ^= 1;
+        assert_eq!(Verifier::verify_with::<FibonacciAir>(&bad, &TEST_POLICY), Err(VerifyError::DigestMismatch));
+    }
+}
@@ -0,0 +1,195 @@
+// File: numiproof-proof/src/trace.rs
+//! Trace commitment: every column is extended onto the LDE domain and each extended row is
+//! hashed into one salted leaf, so a single Merkle path opens all columns of a row, alone or
+//! together with its successor. The LDE domain is a coset off the trace domain, so no leaf is a
+//! raw witness row.
+use numiproof_air::row_to_bytes;
+use numiproof_field::{Fp, TwoAdicField};
+use numiproof_hash::{MerkleHasher, VerifyError, DOM_ROW};
+use numiproof_merkle::MerkleTree;
+use numiproof_poly::batch_coset_lde;
+use rand::RngCore;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{bytes_to_fps, Opening};
+
+/// Openings of the rows at `x` and `x·g`; on the LDE domain the latter sits `row_step` indices
+/// further along (cyclically).
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TraceQuery {
+    pub row: Opening,
+    pub next: Opening,
+}
+
+/// Prover-side trace LDE with its row tree.
+pub struct TraceCommitment<F = Fp> {
+    cols: Vec<Vec<F>>,
+    rows: Vec<Vec<u8>>,
+    tree: MerkleTree,
+    row_step: usize,
+}
+
+impl<F: TwoAdicField> TraceCommitment<F> {
+    /// Extend the trace columns (evaluations over the trace subgroup, padded by repeating the
+    /// last row) onto the coset `coset_shift·<w>` with [`batch_coset_lde`] and commit. Trace row
+    /// `i` corresponds to LDE index `i << blowup_log2`, but no leaf holds it.
+    pub fn from_trace<H: MerkleHasher, R: RngCore + ?Sized>(hasher: &H, columns: &[Vec<F>], blowup_log2: u32, rng: &mut R) -> Self {
+        let cols = batch_coset_lde(columns, blowup_log2, F::coset_shift());
+        Self::from_lde(hasher, cols, 1 << blowup_log2, rng)
+    }
+
+    /// Commit to columns already evaluated over an LDE domain of equal length, on which
+    /// consecutive trace rows are `row_step` indices apart.
+    pub fn from_lde<H: MerkleHasher, R: RngCore + ?Sized>(hasher: &H, cols: Vec<Vec<F>>, row_step: usize, rng: &mut R) -> Self {
+        let len = cols.first().map_or(0, Vec::len);
+        assert!(len > 0 && cols.iter().all(|c| c.len() == len), "columns must share a non-empty domain");
+        assert!(row_step > 0 && row_step < len);
+        let rows: Vec<Vec<u8>> = (0..len)
+            .into_par_iter()
+            .map(|k| row_to_bytes(&cols.iter().map(|c| c[k]).collect::<Vec<_>>()))
+            .collect();
+        let leaves: Vec<Vec<u8>> = rows.par_iter().map(|row| row_leaf(hasher, row)).collect();
+        let tree = MerkleTree::build_salted(hasher, &leaves, rng);
+        Self { cols, rows, tree, row_step }
+    }
+
+    pub fn root(&self) -> Vec<u8> { self.tree.root() }
+    /// Size of the LDE domain.
+    pub fn len(&self) -> usize { self.rows.len() }
+    pub fn is_empty(&self) -> bool { self.rows.is_empty() }
+    pub fn row_step(&self) -> usize { self.row_step }
+    pub fn columns(&self) -> &[Vec<F>] { &self.cols }
+
+    /// Values of every column at LDE index `idx`.
+    pub fn row(&self, idx: usize) -> Vec<F> {
+        self.cols.iter().map(|c| c[idx]).collect()
+    }
+
+    /// Index of the row following `idx`.
+    pub fn next_index(&self, idx: usize) -> usize {
+        (idx + self.row_step) % self.len()
+    }
+
+    /// Open the row at LDE index `idx`: its encoded values, salt and Merkle path.
+    pub fn open(&self, idx: usize) -> Opening {
+        Opening {
+            idx,
+            row: self.rows[idx].clone(),
+            salt: self.tree.salt(idx).expect("trace tree is salted"),
+            path_row: self.tree.open(idx),
+        }
+    }
+
+    /// Open the rows at `idx` and at the following index.
+    pub fn open_with_next(&self, idx: usize) -> TraceQuery {
+        TraceQuery { row: self.open(idx), next: self.open(self.next_index(idx)) }
+    }
+
+    /// Check a row opening against `root` over an LDE domain of `len` rows and decode its
+    /// `n_cols` values.
+    pub fn verify<H: MerkleHasher>(hasher: &H, root: &[u8], n_cols: usize, len: usize, opening: &Opening) -> Result<Vec<F>, VerifyError> {
+        if opening.idx >= len {
+            return Err(VerifyError::MerklePathMismatch { query: opening.idx });
+        }
+        if opening.path_row.len() != len.next_power_of_two().trailing_zeros() as usize {
+            return Err(VerifyError::MalformedEncoding("trace path"));
+        }
+        MerkleTree::verify_salted(hasher, root, opening.idx, &row_leaf(hasher, &opening.row), &opening.salt, &opening.path_row)?;
+        match bytes_to_fps(&opening.row) {
+            Some(r) if r.len() == n_cols => Ok(r),
+            _ => Err(VerifyError::MalformedEncoding("opened row")),
+        }
+    }
+
+    /// Check both openings of `query` under `root`, on an LDE domain of `len` rows with
+    /// successors `row_step` apart, and return the two rows.
+    pub fn verify_query<H: MerkleHasher>(
+        hasher: &H,
+        root: &[u8],
+        n_cols: usize,
+        len: usize,
+        row_step: usize,
+        query: &TraceQuery,
+    ) -> Result<(Vec<F>, Vec<F>), VerifyError> {
+        let row_idx = query.row.idx;
+        if row_idx >= len || query.next.idx != (row_idx + row_step) % len {
+            return Err(VerifyError::MerklePathMismatch { query: row_idx });
+        }
+        Ok((Self::verify(hasher, root, n_cols, len, &query.row)?, Self::verify(hasher, root, n_cols, len, &query.next)?))
+    }
+}
+
+pub(crate) fn row_leaf<H: MerkleHasher>(hasher: &H, row: &[u8]) -> Vec<u8> {
+    hasher.hash_many(DOM_ROW, &[row])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use numiproof_air::{Air, FibonacciAir};
+    use numiproof_hash::HashFunction;
+
+    const H: HashFunction = HashFunction::Shake256_384;
+
+    #[test]
+    fn rows_and_next_rows_open_under_one_root() {
+        let air = FibonacciAir::new(1, 1, 15);
+        let trace = air.gen_trace();
+        let mut rng = rand::thread_rng();
+        let tc = TraceCommitment::from_trace(&H, &trace, 2, &mut rng);
+        assert_eq!((tc.len(), tc.row_step()), (64, 4));
+        assert_eq!(tc.columns(), &batch_coset_lde(&trace, 2, Fp::coset_shift())[..]);
+        let root = tc.root();
+        for idx in [0usize, 20, 63] {
+            let q = tc.open_with_next(idx);
+            assert_eq!(q.next.idx, tc.next_index(idx));
+            let (row, next) = <TraceCommitment>::verify_query(&H, &root, 2, tc.len(), 4, &q).unwrap();
+            assert_eq!((row, next), (tc.row(idx), tc.row(tc.next_index(idx))));
+            // The coset misses the trace subgroup, so no leaf is a witness row
+            assert!((0..16).all(|i| tc.row(idx) != vec![trace[0][i], trace[1][i]]));
+        }
+        // Wrapping around the end of the domain
+        assert_eq!(tc.open_with_next(63).next.idx, 3);
+        // Paths of the wrong height are rejected before hashing
+        let mut bad = tc.open(20);
+        bad.path_row.pop();
+        assert_eq!(<TraceCommitment>::verify(&H, &root, 2, tc.len(), &bad), Err(VerifyError::MalformedEncoding("trace path")));
+        let mut bad = tc.open(20);
+        bad.path_row.push(bad.path_row[0].clone());
+        assert_eq!(<TraceCommitment>::verify(&H, &root, 2, tc.len(), &bad), Err(VerifyError::MalformedEncoding("trace path")));
+    }
+
+    #[test]
+    fn mismatched_next_row_rejected() {
+        let air = FibonacciAir::new(2, 3, 7);
+        let mut rng = rand::thread_rng();
+        let tc = TraceCommitment::from_trace(&H, &air.gen_trace(), 1, &mut rng);
+        let root = tc.root();
+        let mut q = tc.open_with_next(5);
+        q.next = tc.open(6);
+        assert!(<TraceCommitment>::verify_query(&H, &root, 2, tc.len(), 2, &q).is_err());
+        let mut q = tc.open_with_next(5);
+        q.next.row[0] ^= 1;
+        assert!(<TraceCommitment>::verify_query(&H, &root, 2, tc.len(), 2, &q).is_err());
+        let q = tc.open_with_next(5);
+        assert!(<TraceCommitment>::verify_query(&H, &root, 3, tc.len(), 2, &q).is_err());
+    }
+
+    #[test]
+    fn non_canonical_row_encoding_rejected() {
+        use numiproof_field::MODULUS;
+        // A committed row encoding 0 as MODULUS opens under the root but must not decode
+        let canonical = [0u64.to_le_bytes(), 1u64.to_le_bytes()].concat();
+        let aliased = [MODULUS.to_le_bytes(), 1u64.to_le_bytes()].concat();
+        let mut rng = rand::thread_rng();
+        let tree = MerkleTree::build_salted(&H, &[row_leaf(&H, &canonical), row_leaf(&H, &aliased)], &mut rng);
+        let root = tree.root();
+        let open = |idx: usize, row: &[u8]| Opening { idx, row: row.to_vec(), salt: tree.salt(idx).unwrap(), path_row: tree.open(idx) };
+        assert_eq!(<TraceCommitment>::verify(&H, &root, 2, 2, &open(0, &canonical)).unwrap(), vec![Fp::zero(), Fp::one()]);
+        assert_eq!(
+            <TraceCommitment>::verify(&H, &root, 2, 2, &open(1, &aliased)),
+            Err(VerifyError::MalformedEncoding("opened row"))
+        );
+    }
+}
@@ -1,19 +1,24 @@
 use serde::{Serialize, Deserialize};
-use numiproof_air::Air;
-use numiproof_field::Fp;
-use numiproof_hash::{h_many, shake256_384, DIGEST_LEN};
+use numiproof_air::{Air, Assertion};
+use numiproof_field::{ExtensionField, Fp};
+use numiproof_hash::{h_many, DIGEST_LEN, DOM_ACCUMULATOR};
 
 /// Public inputs for recursive proof verification
 #[derive(Clone, Serialize, Deserialize)]
 pub struct RecursivePublic {
     pub prev_digest: Vec<u8>,
     pub cur_digest: Vec<u8>,
+    /// Digest of the inner proof being folded into the accumulator
+    pub inner_digest: Vec<u8>,
     pub inner_proof_root: Vec<u8>,
+    pub steps: u32,
 }
 
 /// AIR for verifying an inner proof recursively (simplified model).
-/// Trace columns: 6 digest limbs plus 2 hash-state accumulators. This checks accumulator-chain
-/// integrity between `prev_digest` and `cur_digest` and binds to `inner_proof_root` via public input.
+/// Trace columns: 6 digest limbs plus 2 hash-state accumulators. The limbs start at `prev_digest`
+/// and absorb `inner_digest` once per step. `cur_digest` must equal the accumulator of
+/// `prev_digest` and `inner_digest`, which the verifier recomputes; `inner_proof_root` is only
+/// bound through the transcript.
 #[derive(Clone)]
 pub struct RecursiveAir {
     pub steps: usize,
@@ -24,6 +29,8 @@ pub struct RecursiveAir {
 
 impl RecursiveAir {
     pub fn new(prev: Option<&[u8]>, cur: &[u8], inner_root: &[u8], steps: usize) -> Self {
+        // The first and last rows carry different assertions
+        assert!(steps >= 2, "recursive trace needs at least two steps");
         let prev_vec = prev.map(|p| p.to_vec()).unwrap_or_else(|| vec![0u8; DIGEST_LEN]);
         Self { steps, prev: prev_vec, cur: cur.to_vec(), inner_root: inner_root.to_vec() }
     }
@@ -31,43 +38,44 @@ impl RecursiveAir {
     /// Decompose digest into field element limbs
     fn digest_to_limbs(digest: &[u8]) -> [Fp; 6] {
         let mut limbs = [Fp::zero(); 6];
-        for i in 0..6 {
-            let start = i * 8;
-            if start + 8 <= digest.len() {
-                let mut chunk = [0u8; 8];
-                chunk.copy_from_slice(&digest[start..start+8]);
-                limbs[i] = Fp::new(u64::from_le_bytes(chunk));
-            }
+        for (limb, chunk) in limbs.iter_mut().zip(digest.chunks_exact(8)) {
+            *limb = Fp::new(u64::from_le_bytes(chunk.try_into().unwrap()));
         }
         limbs
     }
-    
-    /// Recompose limbs back to digest
-    fn limbs_to_digest(limbs: &[Fp; 6]) -> Vec<u8> {
-        let mut digest = Vec::with_capacity(48);
-        for limb in limbs.iter() {
-            digest.extend_from_slice(&limb.to_u64().to_le_bytes());
+
+    /// Accumulator after folding `inner` into `prev`; an all-zero `prev` starts a fresh chain.
+    fn accumulate(prev: &[u8], inner: &[u8]) -> Vec<u8> {
+        if prev.iter().all(|&b| b == 0) {
+            h_many(DOM_ACCUMULATOR, &[inner]).to_vec()
+        } else {
+            h_many(DOM_ACCUMULATOR, &[prev, inner]).to_vec()
         }
-        digest
+    }
+
+    /// Expected limbs on the last row: `prev + (steps - 1) * inner`
+    fn final_limbs(pub_inp: &RecursivePublic) -> [Fp; 6] {
+        let prev = Self::digest_to_limbs(&pub_inp.prev_digest);
+        let inner = Self::digest_to_limbs(&pub_inp.inner_digest);
+        let k = Fp::new(pub_inp.steps.saturating_sub(1) as u64);
+        core::array::from_fn(|j| prev[j] + k * inner[j])
     }
 }
 
 impl Air for RecursiveAir {
+    type Field = Fp;
     type PublicInput = RecursivePublic;
-    fn id(&self) -> &'static str { "recursive_v2" }
+    fn id() -> &'static str { "recursive_v2" }
+    fn n_cols() -> usize { 8 } // 6 digest limbs + 2 hash state accumulators
     fn trace_len(&self) -> usize { self.steps }
-    fn n_cols(&self) -> usize { 8 } // 6 digest limbs + 2 hash state accumulators
     
     fn public_input(&self) -> Self::PublicInput {
-        let new_digest = if self.prev.is_empty() || self.prev.iter().all(|&b| b == 0) {
-            h_many("accumulator", &[&self.cur]).to_vec()
-        } else {
-            h_many("accumulator", &[&self.prev, &self.cur]).to_vec()
-        };
         RecursivePublic {
             prev_digest: self.prev.clone(),
-            cur_digest: new_digest,
+            cur_digest: Self::accumulate(&self.prev, &self.cur),
+            inner_digest: self.cur.clone(),
             inner_proof_root: self.inner_root.clone(),
+            steps: self.steps as u32,
         }
     }
     
@@ -81,90 +89,97 @@ impl Air for RecursiveAir {
             cols[j][0] = limb;
         }
         
-        // Compute accumulator chain: hash prev with cur iteratively
+        // Compute accumulator chain: absorb the inner digest once per step
         let cur_limbs = Self::digest_to_limbs(&self.cur);
         
         for i in 1..n {
             // Each step accumulates: combine previous digest with current
             // Simplified: just propagate and mix limbs
-            for j in 0..6 {
-                let prev_val = cols[j][i-1];
-                let cur_val = cur_limbs[j % 6];
-                // Simple mixing: add with rotation
-                cols[j][i] = prev_val + cur_val;
+            for (j, &cur_val) in cur_limbs.iter().enumerate() {
+                cols[j][i] = cols[j][i-1] + cur_val;
             }
             // Hash state columns: accumulate XOR-like operations in field
             cols[6][i] = cols[6][i-1] + cols[0][i];
             cols[7][i] = cols[7][i-1] + cols[1][i];
         }
         
-        // Final row should match expected output digest
-        let expected_limbs = Self::digest_to_limbs(&self.public_input().cur_digest);
-        for j in 0..6 {
-            cols[j][n-1] = expected_limbs[j];
-        }
-        
         cols
     }
     
-    fn check_row(i: usize, row: &[Fp], next: Option<&[Fp]>, pub_inp: &Self::PublicInput) -> bool {
-        if i == 0 {
-            // First row: verify matches prev_digest
-            let prev_limbs = Self::digest_to_limbs(&pub_inp.prev_digest);
-            for (j, &expected) in prev_limbs.iter().enumerate() {
-                if row[j] != expected { return false; }
-            }
-        }
-        
-        if let Some(nxt) = next {
-            // Transition: verify accumulation consistency
-            let cur_limbs = Self::digest_to_limbs(&pub_inp.cur_digest);
-            for j in 0..6 {
-                let expected_next = row[j] + cur_limbs[j % 6];
-                if nxt[j] != expected_next { return false; }
-            }
-            // Hash state transitions
-            if nxt[6] != row[6] + nxt[0] { return false; }
-            if nxt[7] != row[7] + nxt[1] { return false; }
-        } else {
-            // Last row: verify matches cur_digest
-            let cur_limbs = Self::digest_to_limbs(&pub_inp.cur_digest);
-            for (j, &expected) in cur_limbs.iter().enumerate().take(6) {
-                if row[j] != expected { return false; }
-            }
-        }
-        true
-    }
+    fn transition_degrees() -> Vec<usize> { vec![1; 8] }
     
-    fn eval_constraints(&self, i: usize, row: &[Fp], next: Option<&[Fp]>, pub_inp: &Self::PublicInput) -> Vec<Fp> {
-        let mut constraints = vec![Fp::zero(); 8];
-        
-        if i == 0 {
-            // Boundary constraint: first row matches prev_digest
-            let prev_limbs = Self::digest_to_limbs(&pub_inp.prev_digest);
-            for (j, &expected) in prev_limbs.iter().enumerate() {
-                constraints[j] = row[j] - expected;
-            }
-        }
-        
-        if let Some(nxt) = next {
-            // Transition constraints
-            let cur_limbs = Self::digest_to_limbs(&pub_inp.cur_digest);
-            for j in 0..6 {
-                constraints[j] = nxt[j] - (row[j] + cur_limbs[j % 6]);
-            }
-            constraints[6] = nxt[6] - (row[6] + nxt[0]);
-            constraints[7] = nxt[7] - (row[7] + nxt[1]);
-        } else {
-            // Boundary constraint: last row matches cur_digest
-            let cur_limbs = Self::digest_to_limbs(&pub_inp.cur_digest);
-            for (j, &expected) in cur_limbs.iter().enumerate().take(6) {
-                constraints[j] = row[j] - expected;
-            }
-        }
-        
+    fn eval_transition<E: ExtensionField>(row: &[E], next: &[E], pub_inp: &Self::PublicInput) -> Vec<E> {
+        // Accumulation consistency: each limb absorbs the inner digest once per step
+        let cur_limbs = Self::digest_to_limbs(&pub_inp.inner_digest);
+        let mut constraints: Vec<E> = (0..6).map(|j| next[j] - (row[j] + E::from(cur_limbs[j]))).collect();
+        // Hash state transitions
+        constraints.push(next[6] - (row[6] + next[0]));
+        constraints.push(next[7] - (row[7] + next[1]));
         constraints
     }
+    
+    fn assertions(pub_inp: &Self::PublicInput) -> Vec<Assertion> {
+        // First row matches prev_digest with cleared hash state; last row matches the accumulated chain
+        let prev_limbs = Self::digest_to_limbs(&pub_inp.prev_digest);
+        let final_limbs = Self::final_limbs(pub_inp);
+        let last = pub_inp.steps as usize - 1;
+        let mut out: Vec<Assertion> = prev_limbs.iter().enumerate().map(|(j, &v)| Assertion::new(0, j, v)).collect();
+        out.push(Assertion::new(0, 6, Fp::zero()));
+        out.push(Assertion::new(0, 7, Fp::zero()));
+        out.extend(final_limbs.iter().enumerate().map(|(j, &v)| Assertion::new(last, j, v)));
+        out
+    }
+
+    fn check_public_input(pub_inp: &Self::PublicInput, n_rows: usize) -> bool {
+        pub_inp.steps >= 2
+            && pub_inp.steps as usize == n_rows
+            && pub_inp.cur_digest == Self::accumulate(&pub_inp.prev_digest, &pub_inp.inner_digest)
+    }
 }
 
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use numiproof_proof::{FriConfig, Prover, Verifier, VerifierConfig, VerifyError};
+
+    const POLICY: VerifierConfig = VerifierConfig { min_grinding_bits: 16, min_queries: 1, min_blowup_log2: 1 };
+
+    #[test]
+    fn recursive_trace_satisfies_rows() {
+        let air = RecursiveAir::new(Some(&[3u8; DIGEST_LEN]), &[5u8; DIGEST_LEN], &[7u8; DIGEST_LEN], 8);
+        let pub_inp = air.public_input();
+        let cols = air.gen_trace();
+        let row = |i: usize| cols.iter().map(|c| c[i]).collect::<Vec<_>>();
+        for i in 0..air.trace_len() {
+            let next = if i + 1 < air.trace_len() { Some(row(i + 1)) } else { None };
+            assert!(RecursiveAir::check_row(i, &row(i), next.as_deref(), &pub_inp));
+        }
+    }
+
+    #[test]
+    fn recursive_prove_verify() {
+        let air = RecursiveAir::new(None, &[5u8; DIGEST_LEN], &[7u8; DIGEST_LEN], 16);
+        let prover = Prover { cfg: FriConfig { blowup_log2: 2, num_rounds: 1, queries: 16, ..Default::default() } };
+        let proof = prover.prove(&air);
+        assert!(Verifier::verify_with::<RecursiveAir>(&proof, &POLICY).is_ok());
+    }
+
+    #[test]
+    fn tampered_accumulator_rejected() {
+        let air = RecursiveAir::new(Some(&[3u8; DIGEST_LEN]), &[5u8; DIGEST_LEN], &[7u8; DIGEST_LEN], 8);
+        let prover = Prover { cfg: FriConfig { blowup_log2: 2, num_rounds: 1, queries: 8, ..Default::default() } };
+        let mut proof = prover.prove(&air);
+        assert!(Verifier::verify_with::<RecursiveAir>(&proof, &POLICY).is_ok());
+        let mut pub_inp = air.public_input();
+        assert!(RecursiveAir::check_public_input(&pub_inp, 8));
+        assert!(!RecursiveAir::check_public_input(&pub_inp, 16));
+        pub_inp.cur_digest[0] ^= 1;
+        assert!(!RecursiveAir::check_public_input(&pub_inp, 8));
+        proof.pub_input_enc = bincode::serialize(&pub_inp).unwrap();
+        assert_eq!(Verifier::verify_with::<RecursiveAir>(&proof, &POLICY), Err(VerifyError::MalformedEncoding("public input")));
+        pub_inp.steps = 1;
+        assert!(!RecursiveAir::check_public_input(&pub_inp, 1));
+    }
+}
@@ -1,4 +1,5 @@
-use serde::{Serialize, Deserialize};
+use numiproof_hash::{HashFunction, MAX_GRINDING_BITS};
+use serde::{de::Error as _, Serialize, Deserialize};
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct Params {
@@ -8,14 +9,40 @@ pub struct Params {
     pub fri_rounds: Option<u32>,
     /// Number of query positions for openings
     pub queries: Option<usize>,
+    /// Hash backend by name, e.g. "shake256-384" or "poseidon2"
+    pub hash: Option<String>,
+    /// Proof-of-work bits ground before query sampling
+    pub grinding_bits: Option<u32>,
 }
 
 impl Default for Params {
-    fn default() -> Self { Self { blowup_log2: Some(2), fri_rounds: Some(1), queries: Some(32) } }
+    fn default() -> Self { Self { blowup_log2: Some(2), fri_rounds: Some(1), queries: Some(32), hash: Some("shake256-384".into()), grinding_bits: Some(16) } }
 }
 
-/// Parse prover/verifier parameters from TOML text
+/// Parse prover/verifier parameters from TOML text, rejecting unknown hash names and grinding
+/// above [`MAX_GRINDING_BITS`]
 pub fn load_params_toml(input: &str) -> Result<Params, toml::de::Error> {
-    toml::from_str::<Params>(input)
+    let params = toml::from_str::<Params>(input)?;
+    if let Some(h) = &params.hash {
+        h.parse::<HashFunction>().map_err(toml::de::Error::custom)?;
+    }
+    if params.grinding_bits.is_some_and(|b| b > MAX_GRINDING_BITS) {
+        return Err(toml::de::Error::custom(format!("grinding_bits above {MAX_GRINDING_BITS}")));
+    }
+    Ok(params)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn params_are_validated() {
+        let p = load_params_toml("hash = \"poseidon2\"\ngrinding_bits = 20").unwrap();
+        assert_eq!((p.hash.as_deref(), p.grinding_bits, p.queries), (Some("poseidon2"), Some(20), None));
+        assert!(load_params_toml("hash = \"md5\"").unwrap_err().to_string().contains("unknown hash function"));
+        assert!(load_params_toml("grinding_bits = 33").unwrap_err().to_string().contains("grinding_bits above 32"));
+        assert!(load_params_toml("queries = \"many\"").is_err());
+    }
 }
 